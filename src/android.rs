@@ -1,5 +1,9 @@
-use crate::{Browser, BrowserOptions, Error, ErrorKind, Result, TargetType};
+use crate::{
+    Browser, BrowserFamily, BrowserOptions, ErrorKind, HandlerKind, OpenOutcome, ResolvedTarget,
+    Result, TargetType,
+};
 use jni::objects::{JObject, JValue};
+use std::io::Error;
 use std::process::{Command, Stdio};
 
 /// Deal with opening of browsers on Android. Only [Browser::Default] is supported, and
@@ -8,19 +12,76 @@ pub(super) fn open_browser_internal(
     browser: Browser,
     target: &TargetType,
     options: &BrowserOptions,
-) -> Result<()> {
+) -> Result<OpenOutcome> {
     // ensure we're opening only http/https urls, failing otherwise
     let url = target.get_http_url()?;
 
     match browser {
-        Browser::Default => open_browser_default(url, options),
-        _ => Err(Error::new(
+        // the intent may be resolved to a non-browser app, e.g. via app links
+        Browser::Default => open_browser_default(url, options)
+            .map(|_| OpenOutcome::new(HandlerKind::OsDefault, None)),
+        Browser::Firefox
+        | Browser::InternetExplorer
+        | Browser::Chrome
+        | Browser::Opera
+        | Browser::Safari
+        | Browser::WebPositive
+        | Browser::Brave
+        | Browser::Edge
+        | Browser::TorBrowser
+        | Browser::Epiphany
+        | Browser::Konqueror => Err(Error::new(
             ErrorKind::NotFound,
             "only default browser supported",
         )),
     }
 }
 
+/// The default browser can't be identified on this platform
+pub(super) fn default_browser_family() -> BrowserFamily {
+    BrowserFamily::Unknown
+}
+
+/// The default browser can't be identified on this platform
+pub(super) fn default_browser_name() -> Result<String> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "default browser can't be identified on this platform",
+    ))
+}
+
+/// Browsers aren't launched via executables on this platform
+pub(super) fn browser_path(_browser: Browser) -> Result<std::path::PathBuf> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "browser executables can't be resolved on this platform",
+    ))
+}
+
+/// Urls can't be opened via commands on this platform
+pub(super) fn open_with_command_internal(
+    _command: &str,
+    _target: &TargetType,
+    _options: &BrowserOptions,
+) -> Result<OpenOutcome> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "urls can't be opened via commands on this platform",
+    ))
+}
+
+/// Urls aren't opened by running commands on this platform
+pub(super) fn resolve_internal(
+    _browser: Browser,
+    _target: &TargetType,
+    _options: &BrowserOptions,
+) -> Result<ResolvedTarget> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "commands can't be resolved on this platform",
+    ))
+}
+
 /// Open the default browser
 fn open_browser_default(url: &str, options: &BrowserOptions) -> Result<()> {
     // always return true for a dry run
@@ -34,6 +95,32 @@ fn open_browser_default(url: &str, options: &BrowserOptions) -> Result<()> {
         return Ok(());
     }
 
+    start_view_intent(url, false)
+}
+
+/// Open a deep link (e.g. `myapp://path`) in the app registered for it. Unlike
+/// [open_browser_internal], this isn't restricted to http(s) urls, and returns an
+/// error of kind [ErrorKind::NotFound] if no activity can handle the url.
+pub(super) fn open_deep_link(target: &TargetType, options: &BrowserOptions) -> Result<()> {
+    // under termux, the android context may not have been initialized, and we've
+    // no way to check if the url can be handled, so we let the caller fall back
+    if std::env::var("TERMUX_VERSION").is_ok() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            "deep links not supported under termux",
+        ));
+    }
+
+    if options.dry_run {
+        return Ok(());
+    }
+
+    start_view_intent(target.0.as_str(), true)
+}
+
+/// Create an ACTION_VIEW intent for `url` and start it. If `ensure_resolvable` is
+/// true, the intent is started only if there's an activity to handle it.
+fn start_view_intent(url: &str, ensure_resolvable: bool) -> Result<()> {
     // Create a VM for executing Java calls
     let ctx = ndk_context::android_context();
     let vm = unsafe { jni::JavaVM::from_raw(ctx.vm() as _) }.map_err(|_| {
@@ -84,6 +171,33 @@ fn open_browser_default(url: &str, options: &BrowserOptions) -> Result<()> {
     )
     .map_err(|_| Error::new(ErrorKind::Other, "Failed to initialize intent"))?;
 
+    // Ensure that some activity can handle the intent, if asked to
+    if ensure_resolvable {
+        let package_manager = env
+            .call_method(
+                &activity,
+                "getPackageManager",
+                "()Landroid/content/pm/PackageManager;",
+                &[],
+            )
+            .map_err(|_| Error::new(ErrorKind::Other, "Failed to get package manager"))?;
+        let component = env
+            .call_method(
+                &intent,
+                "resolveActivity",
+                "(Landroid/content/pm/PackageManager;)Landroid/content/ComponentName;",
+                &[package_manager.borrow()],
+            )
+            .and_then(|component| component.l())
+            .map_err(|_| Error::new(ErrorKind::Other, "Failed to resolve activity"))?;
+        if component.is_null() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                "no activity found to handle url",
+            ));
+        }
+    }
+
     // Start the intent activity.
     env.call_method(
         &activity,
@@ -0,0 +1,175 @@
+//! Support for the common pattern (e.g. OAuth flows in CLI tools) of opening a url in the
+//! browser, and then waiting for the browser to be redirected to a local callback url.
+
+use crate::{open, ErrorKind, Result};
+use std::io::{BufRead, BufReader, Error, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Details of the callback request received by [open_and_wait_for_callback]
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct HttpRequestInfo {
+    method: String,
+    path: String,
+    query: String,
+}
+
+impl HttpRequestInfo {
+    /// The HTTP method of the request, e.g. `GET`
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// The path of the request, e.g. `/callback`
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The raw query string of the request (without the leading `?`), or an empty string
+    /// if there was none
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// The percent-decoded value of the first query parameter named `name`, if present
+    pub fn query_param(&self, name: &str) -> Option<String> {
+        url::form_urlencoded::parse(self.query.as_bytes())
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+    }
+}
+
+/// Opens `url` in the default browser, and blocks until a single HTTP request is received on
+/// `bind_addr`, returning the details of that request. This is typically used in OAuth flows,
+/// where `url` eventually redirects the browser to something like `http://127.0.0.1:8080/callback`.
+///
+/// The listener is bound before the browser is opened, so the callback can't be missed. Requests
+/// for `/favicon.ico`, and connections which don't send a request within a few seconds (e.g.
+/// speculative connections made by browsers), are ignored. The browser is sent a minimal page
+/// asking the user to close the window.
+///
+/// This is available only with the `callback` feature enabled.
+///
+/// # Examples
+/// ```no_run
+/// let req = webbrowser::open_and_wait_for_callback(
+///     "https://example.com/oauth/authorize?redirect_uri=http://127.0.0.1:8080/callback",
+///     "127.0.0.1:8080",
+/// )
+/// .expect("failed to receive callback");
+/// let code = req.query_param("code");
+/// ```
+pub fn open_and_wait_for_callback<A: ToSocketAddrs>(
+    url: &str,
+    bind_addr: A,
+) -> Result<HttpRequestInfo> {
+    let listener = TcpListener::bind(bind_addr)?;
+    open(url)?;
+    wait_for_callback(&listener)
+}
+
+/// Wait until a valid callback request is received on `listener`
+fn wait_for_callback(listener: &TcpListener) -> Result<HttpRequestInfo> {
+    for stream in listener.incoming() {
+        let mut stream = stream?;
+        match read_request(&stream) {
+            Ok(req) if req.path != "/favicon.ico" => {
+                let _ = stream.write_all(CALLBACK_RESPONSE.as_bytes());
+                return Ok(req);
+            }
+            Ok(req) => {
+                log::trace!("ignoring callback request for {}", req.path);
+                let _ = stream.write_all(NOT_FOUND_RESPONSE.as_bytes());
+            }
+            Err(err) => log::debug!("ignoring invalid callback request: {}", err),
+        }
+    }
+    Err(Error::new(ErrorKind::Other, "callback listener closed"))
+}
+
+/// Read the request line and headers of an HTTP request from `stream`
+fn read_request(stream: &TcpStream) -> Result<HttpRequestInfo> {
+    stream.set_read_timeout(Some(REQUEST_READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+
+    // request line is of the form: GET /callback?code=xyz HTTP/1.1
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_ascii_whitespace();
+    let (method, target) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(method), Some(target), Some(version)) if version.starts_with("HTTP/") => {
+            (method, target)
+        }
+        _ => return Err(Error::new(ErrorKind::InvalidData, "invalid request line")),
+    };
+    let (path, query) = match target.find('?') {
+        Some(idx) => (&target[..idx], &target[idx + 1..]),
+        None => (target, ""),
+    };
+
+    // consume the headers, so that the browser doesn't see a reset connection
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 && line.trim_end() != "" {
+        line.clear();
+    }
+
+    Ok(HttpRequestInfo {
+        method: method.to_owned(),
+        path: path.to_owned(),
+        query: query.to_owned(),
+    })
+}
+
+/// How long we wait for an accepted connection to send its request
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+const CALLBACK_RESPONSE: &str = "HTTP/1.1 200 OK\r\n\
+    Content-Type: text/html; charset=utf-8\r\n\
+    Connection: close\r\n\
+    \r\n\
+    <html><body><p>You can close this window now.</p></body></html>";
+
+const NOT_FOUND_RESPONSE: &str = "HTTP/1.1 404 Not Found\r\n\
+    Content-Length: 0\r\n\
+    Connection: close\r\n\
+    \r\n";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn send_request(addr: std::net::SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(addr).expect("failed to connect");
+        stream
+            .write_all(request.as_bytes())
+            .expect("failed to write request");
+        let mut response = String::new();
+        let _ = stream.read_to_string(&mut response);
+        response
+    }
+
+    #[test]
+    fn test_wait_for_callback() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind");
+        let addr = listener.local_addr().expect("failed to get local addr");
+        let client = std::thread::spawn(move || {
+            let favicon = send_request(addr, "GET /favicon.ico HTTP/1.1\r\nHost: x\r\n\r\n");
+            assert!(favicon.starts_with("HTTP/1.1 404"));
+            let callback = send_request(
+                addr,
+                "GET /callback?code=a%20b&state=xyz HTTP/1.1\r\nHost: x\r\n\r\n",
+            );
+            assert!(callback.starts_with("HTTP/1.1 200"));
+        });
+
+        let req = wait_for_callback(&listener).expect("failed to get callback");
+        client.join().expect("client failed");
+        assert_eq!(req.method(), "GET");
+        assert_eq!(req.path(), "/callback");
+        assert_eq!(req.query(), "code=a%20b&state=xyz");
+        assert_eq!(req.query_param("code").as_deref(), Some("a b"));
+        assert_eq!(req.query_param("state").as_deref(), Some("xyz"));
+        assert_eq!(req.query_param("missing"), None);
+    }
+}
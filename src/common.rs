@@ -1,6 +1,10 @@
-use super::{BrowserOptions, Error, ErrorKind, Result};
+use super::{BrowserFamily, BrowserOptions, ErrorKind, Result, TEXT_BROWSERS};
 use log::debug;
-use std::process::{Command, Stdio};
+use std::ffi::{OsStr, OsString};
+use std::io::Error;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
 
 /// Parses `line` to find tokens (including quoted strings), and invokes `op`
 /// on each token
@@ -44,17 +48,89 @@ where
     }
 }
 
-/// Run the specified command in foreground/background
+/// Run the specified command in foreground/background, returning the pid of the spawned process
+/// if run in the background
 pub(crate) fn run_command(
     cmd: &mut Command,
     background: bool,
     options: &BrowserOptions,
-) -> Result<()> {
+) -> Result<Option<u32>> {
+    let family = BrowserFamily::from_name(&cmd.get_program().to_string_lossy());
+    // the debugging port must reach the browser, which only chromium accepts it as a flag
+    if options.remote_debugging_port.is_some() && family != BrowserFamily::Chromium {
+        return Err(Error::new(
+            ErrorKind::Unsupported,
+            format!(
+                "remote debugging is supported only for Chromium based browsers, not {:?}",
+                cmd.get_program()
+            ),
+        ));
+    }
+    configure_env(cmd, options);
+    configure_args(cmd, options);
+    let temporary_profile = configure_firefox_profile(cmd, options)?;
+
+    // text browsers run in the terminal, so startup notification isn't relevant for them
+    #[cfg(unix)]
+    if background && options.startup_notify {
+        configure_startup_notify(cmd);
+    }
+
+    if let Some(wrapper) = &options.wrapper_command {
+        let mut prefix = Vec::new();
+        for_each_token(wrapper, |token| prefix.push(token.to_owned()));
+        if !prefix.is_empty() {
+            *cmd = prefix_command(&prefix, cmd);
+        }
+    }
+
+    // text browsers need to stay attached to our terminal, so we don't move them into a scope
+    #[cfg(unix)]
+    if background && !options.no_path_search {
+        if let Some(properties) = &options.systemd_scope {
+            configure_systemd_scope(cmd, properties);
+        }
+    }
+
+    #[cfg(unix)]
+    if let Some(user) = deescalation_user(options) {
+        if let Some(uid) = options.target_user {
+            configure_session_env(cmd, uid);
+        }
+        *cmd = deescalate_command(cmd, &user);
+    }
+
+    // the commands rewritten above only carry over the env vars which were set explicitly
+    if options.clean_env {
+        clear_inherited_env(cmd);
+    }
+
+    // helpers like sudo or the wrapper command are specified by name, so we reject them here,
+    // rather than let Command search for them in PATH
+    if options.no_path_search && !Path::new(cmd.get_program()).is_absolute() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!(
+                "PATH search disabled, so not running {:?}",
+                cmd.get_program()
+            ),
+        ));
+    }
+
+    crate::apply_command_hook(cmd);
+
     // if dry_run, we return a true, as executable existence check has
     // already been done
     if options.dry_run {
         debug!("dry-run enabled, so not running: {:?}", &cmd);
-        return Ok(());
+        return Ok(None);
+    }
+
+    // only the browsers which get passed the cache dir need it to exist
+    if let Some(cache_dir) = &options.cache_dir {
+        if family == BrowserFamily::Chromium {
+            std::fs::create_dir_all(cache_dir)?;
+        }
     }
 
     if background {
@@ -69,7 +145,23 @@ pub(crate) fn run_command(
             cmd
         }
         .spawn()
-        .map(|_| ())
+        .and_then(|mut child| {
+            if options.wait_for_exit {
+                debug!("waiting for background command to exit");
+                wait_for_exit(&mut child)?;
+                return Ok(None);
+            }
+            if options.verify_launch {
+                check_early_exit(&mut child, EARLY_EXIT_GRACE_PERIOD)?;
+            }
+            // the browser is still using the temporary profile, if any
+            if let Some(profile) = temporary_profile {
+                profile.keep();
+            }
+            let pid = child.id();
+            crate::retain_child(child);
+            Ok(Some(pid))
+        })
     } else {
         debug!("foreground exec: {:?}", &cmd);
         // if we're in foreground, use status() instead of spawn(), as we'd like to wait
@@ -78,7 +170,7 @@ pub(crate) fn run_command(
         // most likely because of a text browser
         cmd.status().and_then(|status| {
             if status.success() {
-                Ok(())
+                Ok(None)
             } else {
                 Err(Error::new(
                     ErrorKind::Other,
@@ -88,3 +180,1584 @@ pub(crate) fn run_command(
         })
     }
 }
+
+/// Runs `cmd` in a transient systemd scope with the specified unit `properties`, via
+/// `systemd-run`, if available
+#[cfg(unix)]
+fn configure_systemd_scope(cmd: &mut Command, properties: &[(String, String)]) {
+    let systemd_run = std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join("systemd-run"))
+            .find(|pb| pb.is_file())
+    });
+    let systemd_run = match systemd_run {
+        Some(pb) => pb,
+        None => {
+            debug!("systemd-run not found, so not launching in a systemd scope");
+            return;
+        }
+    };
+    let mut prefix = vec![
+        systemd_run.to_string_lossy().into_owned(),
+        "--user".to_owned(),
+        "--scope".to_owned(),
+        "--quiet".to_owned(),
+    ];
+    for (name, value) in properties {
+        prefix.push("-p".to_owned());
+        prefix.push(format!("{name}={value}"));
+    }
+    prefix.push("--".to_owned());
+    *cmd = prefix_command(&prefix, cmd);
+}
+
+/// Duration for which we watch a background command for an early exit, if asked to via
+/// [BrowserOptions::with_verify_launch]
+const EARLY_EXIT_GRACE_PERIOD: Duration = Duration::from_millis(100);
+
+/// Watches `child` for up to `grace_period`, returning an error if it exits unsuccessfully in
+/// that time, so that other options can be tried. Note that browsers which are already running
+/// typically hand the url over to the existing instance, and exit immediately with a zero exit
+/// code, which we treat as success, so as to not open the url twice.
+fn check_early_exit(child: &mut Child, grace_period: Duration) -> Result<()> {
+    let start = Instant::now();
+    loop {
+        match child.try_wait()? {
+            Some(status) if status.success() => {
+                debug!("background command exited successfully: {}", status);
+                return Ok(());
+            }
+            Some(status) => {
+                debug!("background command failed: {}", status);
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    "command present but exited unsuccessfully",
+                ));
+            }
+            None if start.elapsed() >= grace_period => return Ok(()),
+            None => std::thread::sleep(Duration::from_millis(10)),
+        }
+    }
+}
+
+/// Blocks till `child` exits, returning an error if it exits unsuccessfully
+fn wait_for_exit(child: &mut Child) -> Result<()> {
+    let status = child.wait()?;
+    debug!("background command exited: {}", status);
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::new(
+            ErrorKind::Other,
+            format!("browser exited unsuccessfully ({status})"),
+        ))
+    }
+}
+
+/// Set up the environment of the browser process as per `options`
+fn configure_env(cmd: &mut Command, options: &BrowserOptions) {
+    if options.clean_env {
+        cmd.env_clear();
+        for var in CLEAN_ENV_VARS {
+            if let Some(value) = std::env::var_os(var) {
+                cmd.env(var, value);
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    forward_session_env(cmd, options, |var| std::env::var_os(var));
+
+    // display has already been validated by the time we reach here
+    #[cfg(unix)]
+    if let Some(display) = &options.display {
+        if let Some(var) = crate::display_env_var(display) {
+            cmd.env(var, display);
+        }
+    }
+
+    if let Some(timezone) = &options.timezone {
+        cmd.env("TZ", timezone);
+    }
+}
+
+/// Stop `cmd` from inheriting our environment, retaining only the env vars set explicitly on it,
+/// which after [configure_env] are the ones we want the browser to see
+fn clear_inherited_env(cmd: &mut Command) {
+    let envs: Vec<(OsString, Option<OsString>)> = cmd
+        .get_envs()
+        .map(|(key, value)| (key.to_owned(), value.map(OsStr::to_owned)))
+        .collect();
+    cmd.env_clear();
+    for (key, value) in envs {
+        if let Some(value) = value {
+            cmd.env(key, value);
+        }
+    }
+}
+
+/// Explicitly set the env vars required to connect to our graphical & D-Bus session on `cmd`, as
+/// looked up via `env_var`, so that they reach the browser (or helpers like `xdg-open`), even if
+/// they'd otherwise get lost along the way, e.g. via the wrapper command. Env vars which have been
+/// explicitly set on `cmd` are left as is, and nothing is forwarded when running the browser as
+/// another user, as our session isn't theirs.
+#[cfg(unix)]
+fn forward_session_env<F>(cmd: &mut Command, options: &BrowserOptions, env_var: F)
+where
+    F: Fn(&str) -> Option<OsString>,
+{
+    if options.deescalate || options.target_user.is_some() {
+        return;
+    }
+    for var in SESSION_ENV_VARS {
+        // the display, if specified, is the only one we want to set
+        let is_display = matches!(var, "DISPLAY" | "WAYLAND_DISPLAY");
+        if (is_display && options.display.is_some()) || cmd.get_envs().any(|(key, _)| key == var) {
+            continue;
+        }
+        if let Some(value) = env_var(var) {
+            cmd.env(var, value);
+        }
+    }
+}
+
+/// Env vars which [forward_session_env] forwards to the browser
+#[cfg(unix)]
+const SESSION_ENV_VARS: [&str; 3] = ["DISPLAY", "WAYLAND_DISPLAY", "DBUS_SESSION_BUS_ADDRESS"];
+
+/// Env vars which are retained when [BrowserOptions::with_clean_env] is set
+#[cfg(unix)]
+const CLEAN_ENV_VARS: [&str; 10] = [
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "LANG",
+    "DISPLAY",
+    "WAYLAND_DISPLAY",
+    "XAUTHORITY",
+    "XDG_RUNTIME_DIR",
+    "DBUS_SESSION_BUS_ADDRESS",
+];
+
+/// Env vars which are retained when [BrowserOptions::with_clean_env] is set
+#[cfg(windows)]
+const CLEAN_ENV_VARS: [&str; 10] = [
+    "PATH",
+    "SYSTEMROOT",
+    "WINDIR",
+    "COMSPEC",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "TEMP",
+    "TMP",
+    "PROGRAMFILES",
+];
+
+/// Add browser specific arguments to `cmd` as per `options`, if we recognise the browser
+fn configure_args(cmd: &mut Command, options: &BrowserOptions) {
+    let family = BrowserFamily::from_name(&cmd.get_program().to_string_lossy());
+    let mut args: Vec<OsString> = Vec::new();
+    if options.new_window {
+        match family {
+            // a private window is a new window anyway, and firefox wants the url right after it
+            BrowserFamily::Firefox if !options.incognito => args.push("-new-window".into()),
+            BrowserFamily::Chromium => args.push("--new-window".into()),
+            _ => {}
+        }
+    }
+    if options.incognito {
+        if let Some(arg) = family.incognito_arg() {
+            args.push(arg.into());
+        }
+    }
+    if options.kiosk {
+        if matches!(family, BrowserFamily::Chromium | BrowserFamily::Firefox) {
+            args.push("--kiosk".into());
+        }
+    } else if options.fullscreen {
+        match family {
+            BrowserFamily::Chromium => args.push("--start-fullscreen".into()),
+            BrowserFamily::Firefox => args.push("--kiosk".into()),
+            _ => {}
+        }
+    } else if options.maximized {
+        if family == BrowserFamily::Chromium {
+            args.push("--start-maximized".into());
+        }
+    } else if let Some((x, y, width, height)) = options.window_bounds {
+        match family {
+            BrowserFamily::Chromium => {
+                args.push(format!("--window-position={x},{y}").into());
+                args.push(format!("--window-size={width},{height}").into());
+            }
+            BrowserFamily::Firefox => {
+                args.push("-width".into());
+                args.push(width.to_string().into());
+                args.push("-height".into());
+                args.push(height.to_string().into());
+            }
+            _ => {}
+        }
+    }
+    #[cfg(unix)]
+    if let Some(platform) = options.display_platform {
+        use crate::DisplayPlatform;
+        match (family, platform) {
+            (BrowserFamily::Chromium, DisplayPlatform::Wayland) => {
+                args.push("--ozone-platform=wayland".into())
+            }
+            (BrowserFamily::Chromium, DisplayPlatform::X11) => {
+                args.push("--ozone-platform=x11".into())
+            }
+            (BrowserFamily::Firefox, DisplayPlatform::Wayland) => {
+                cmd.env("MOZ_ENABLE_WAYLAND", "1");
+            }
+            (BrowserFamily::Firefox, DisplayPlatform::X11) => {
+                cmd.env("MOZ_ENABLE_WAYLAND", "0").env("GDK_BACKEND", "x11");
+            }
+            _ => {}
+        }
+    }
+    if options.disable_gpu && family == BrowserFamily::Chromium {
+        args.push("--disable-gpu".into());
+        args.push("--disable-software-rasterizer".into());
+    }
+    if options.force_accessibility && family == BrowserFamily::Chromium {
+        args.push("--force-renderer-accessibility".into());
+    }
+    if let Some(cache_dir) = &options.cache_dir {
+        if family == BrowserFamily::Chromium {
+            let mut arg = OsString::from("--disk-cache-dir=");
+            arg.push(cache_dir);
+            args.push(arg);
+        }
+    }
+    if let Some(port) = options.remote_debugging_port {
+        if family == BrowserFamily::Chromium {
+            args.push(format!("--remote-debugging-port={port}").into());
+        }
+    }
+    // firefox has no flags for these, so they're set as prefs by configure_firefox_profile()
+    if family == BrowserFamily::Chromium {
+        if options.ignore_cert_errors {
+            args.push("--ignore-certificate-errors".into());
+        }
+        if !options.accept_languages.is_empty() {
+            let languages = options.accept_languages.join(",");
+            args.push(format!("--accept-lang={languages}").into());
+        }
+        if options.suppress_first_run {
+            args.push("--no-first-run".into());
+            args.push("--no-default-browser-check".into());
+        }
+    }
+    if let Some(profile) = &options.profile {
+        if family == BrowserFamily::Firefox && firefox_prefs(options).is_empty() {
+            args.push("-P".into());
+            args.push(profile.into());
+        }
+    }
+    if let Some(profile) = &options.profile {
+        if family == BrowserFamily::Chromium {
+            args.push(format!("--profile-directory={profile}").into());
+        }
+    }
+    args.extend(options.args.iter().map(OsString::from));
+    if !args.is_empty() {
+        *cmd = with_leading_args(cmd, &args);
+    }
+}
+
+/// Prefs for the firefox profile used when ignoring certificate errors. Firefox has no way to
+/// ignore such errors altogether, so we allow adding a permanent exception for the certificate
+/// instead, even if the host is on the HSTS preload list.
+const INSECURE_FIREFOX_PREFS: &str = r#"user_pref("security.certerrors.permanentOverride", true);
+user_pref("network.stricttransportsecurity.preloadlist", false);
+user_pref("browser.shell.checkDefaultBrowser", false);
+"#;
+
+/// Prefs for the firefox profile used when suppressing the first run experience, i.e. the
+/// welcome & what's new pages, and the default browser check
+const FIRST_RUN_FIREFOX_PREFS: &str = r#"user_pref("browser.startup.homepage_override.mstone", "ignore");
+user_pref("browser.aboutwelcome.enabled", false);
+user_pref("datareporting.policy.firstRunURL", "");
+user_pref("browser.shell.checkDefaultBrowser", false);
+"#;
+
+/// Returns the prefs which firefox needs to be run with as per `options`, in lieu of flags
+fn firefox_prefs(options: &BrowserOptions) -> String {
+    let mut prefs = String::new();
+    if options.ignore_cert_errors {
+        prefs.push_str(INSECURE_FIREFOX_PREFS);
+    }
+    if !options.accept_languages.is_empty() {
+        let languages = options.accept_languages.join(",");
+        prefs.push_str(&format!(
+            "user_pref(\"intl.accept_languages\", \"{languages}\");\n"
+        ));
+    }
+    if options.suppress_first_run {
+        prefs.push_str(FIRST_RUN_FIREFOX_PREFS);
+    }
+    prefs
+}
+
+/// Runs firefox with a temporary profile holding the prefs it needs as per `options`, if any,
+/// returning the profile, which gets removed once dropped. The profile isn't created for dry
+/// runs. An error of kind [ErrorKind::InvalidInput] is returned if a named profile has been
+/// specified too, as firefox can't be run with both.
+fn configure_firefox_profile(
+    cmd: &mut Command,
+    options: &BrowserOptions,
+) -> Result<Option<TemporaryProfile>> {
+    let family = BrowserFamily::from_name(&cmd.get_program().to_string_lossy());
+    let prefs = firefox_prefs(options);
+    if family != BrowserFamily::Firefox || prefs.is_empty() {
+        return Ok(None);
+    }
+    if options.profile.is_some() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "firefox can't be run with a named profile, along with options needing a temporary one",
+        ));
+    }
+
+    let profile = if options.dry_run {
+        None
+    } else {
+        Some(TemporaryProfile::create(&prefs)?)
+    };
+    let path = match &profile {
+        Some(profile) => profile.path().to_owned(),
+        None => temporary_profile_path(),
+    };
+    *cmd = with_leading_args(cmd, &[OsStr::new("-profile"), path.as_os_str()]);
+    Ok(profile)
+}
+
+/// Returns a path for a temporary firefox profile, which is hard to guess, so that other users
+/// can't create it beforehand
+fn temporary_profile_path() -> std::path::PathBuf {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    // the keys of RandomState are random, which is all we need here
+    let mut hasher = RandomState::new().build_hasher();
+    if let Ok(elapsed) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+        hasher.write_u128(elapsed.as_nanos());
+    }
+    std::env::temp_dir().join(format!(
+        "webbrowser-firefox-profile.{}.{:016x}",
+        std::process::id(),
+        hasher.finish()
+    ))
+}
+
+/// A temporary firefox profile, which is removed when dropped, unless kept via [Self::keep]
+#[derive(Debug)]
+struct TemporaryProfile(Option<std::path::PathBuf>);
+
+impl TemporaryProfile {
+    /// Creates a new profile directory, accessible only to us, with `prefs` in its `user.js`.
+    /// An existing directory is never reused, as it could've been set up by someone else.
+    fn create(prefs: &str) -> Result<Self> {
+        #[cfg(unix)]
+        let builder = {
+            let mut builder = std::fs::DirBuilder::new();
+            std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+            builder
+        };
+        // the temp dir is private to each user on windows
+        #[cfg(not(unix))]
+        let builder = std::fs::DirBuilder::new();
+        let mut attempts = 0;
+        let path = loop {
+            let path = temporary_profile_path();
+            match builder.create(&path) {
+                Ok(()) => break path,
+                Err(err) if err.kind() == ErrorKind::AlreadyExists && attempts < 8 => {
+                    attempts += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        };
+        let profile = TemporaryProfile(Some(path));
+        let mut user_js = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(profile.path().join("user.js"))?;
+        std::io::Write::write_all(&mut user_js, prefs.as_bytes())?;
+        Ok(profile)
+    }
+
+    /// Returns the path of the profile directory
+    fn path(&self) -> &Path {
+        self.0.as_deref().unwrap_or_else(|| Path::new(""))
+    }
+
+    /// Leaves the profile in place, for a browser which is still running with it
+    fn keep(mut self) {
+        if let Some(path) = self.0.take() {
+            debug!("leaving temporary firefox profile in place: {:?}", path);
+        }
+    }
+}
+
+impl Drop for TemporaryProfile {
+    fn drop(&mut self) {
+        if let Some(path) = self.0.take() {
+            let _ = std::fs::remove_dir_all(path);
+        }
+    }
+}
+
+/// Returns true if specified command refers to a known list of text browsers
+pub(crate) fn is_text_browser(pb: &Path) -> bool {
+    // on windows, the executable name is case insensitive, and has an `.exe` extension
+    #[cfg(windows)]
+    let name = pb.with_extension("").to_string_lossy().to_ascii_lowercase();
+    #[cfg(windows)]
+    let pb = Path::new(&name);
+    TEXT_BROWSERS.iter().any(|browser| pb.ends_with(browser))
+}
+
+/// Returns a copy of `cmd`, with `args` inserted before its existing arguments
+fn with_leading_args<S: AsRef<OsStr>>(cmd: &Command, args: &[S]) -> Command {
+    let mut updated = Command::new(cmd.get_program());
+    updated.args(args).args(cmd.get_args());
+    copy_env_and_dir(cmd, &mut updated);
+    updated
+}
+
+/// Copy the env vars and current dir of `from` to `to`
+fn copy_env_and_dir(from: &Command, to: &mut Command) {
+    for (key, value) in from.get_envs() {
+        match value {
+            Some(value) => to.env(key, value),
+            None => to.env_remove(key),
+        };
+    }
+    if let Some(dir) = from.get_current_dir() {
+        to.current_dir(dir);
+    }
+}
+
+/// Set up startup notification for `cmd`, so that the desktop environment can show launch
+/// feedback, and apply focus stealing prevention. See the [spec](https://specifications.freedesktop.org/startup-notification-spec/latest/)
+/// for details.
+#[cfg(unix)]
+fn configure_startup_notify(cmd: &mut Command) {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    static SEQUENCE: AtomicU32 = AtomicU32::new(0);
+
+    let program = Path::new(cmd.get_program())
+        .file_name()
+        .map(|name| name.to_string_lossy().replace(char::is_whitespace, "_"))
+        .unwrap_or_default();
+    let id = format!(
+        "webbrowser-{}-{}-{}-{}",
+        std::process::id(),
+        get_hostname(),
+        program,
+        SEQUENCE.fetch_add(1, Ordering::Relaxed)
+    );
+    if program == "exo-open" {
+        // exo-open expects the startup id to be passed on explicitly
+        *cmd = with_leading_args(cmd, &["--startup-id", &id]);
+    }
+    cmd.env("DESKTOP_STARTUP_ID", id);
+}
+
+/// Returns the hostname of this machine, as required in startup notification ids
+#[cfg(unix)]
+fn get_hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| std::fs::read_to_string("/etc/hostname"))
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .map(|hostname| hostname.trim().to_owned())
+        .filter(|hostname| !hostname.is_empty())
+        .unwrap_or_else(|| "localhost".to_owned())
+}
+
+/// Returns the user who invoked us via `sudo`, if that's the case
+#[cfg(unix)]
+pub(crate) fn sudo_user() -> Option<String> {
+    std::env::var("SUDO_USER")
+        .ok()
+        .filter(|user| !user.is_empty() && user != "root")
+}
+
+/// Returns the user to whom we need to deescalate to, i.e. the [BrowserOptions::with_target_user]
+/// if set, else the user who invoked `sudo`, if [BrowserOptions::with_deescalate] has been set
+#[cfg(unix)]
+pub(crate) fn deescalation_user(options: &BrowserOptions) -> Option<String> {
+    if let Some(uid) = options.target_user {
+        let passwd = std::fs::read_to_string("/etc/passwd").ok()?;
+        parse_passwd_user(&passwd, uid)
+    } else if options.deescalate {
+        sudo_user()
+    } else {
+        None
+    }
+}
+
+/// Returns the uid & home directory of `user` as per `/etc/passwd`
+#[cfg(unix)]
+pub(crate) fn get_user_entry(user: &str) -> Option<(u32, std::path::PathBuf)> {
+    let passwd = std::fs::read_to_string("/etc/passwd").ok()?;
+    parse_passwd_entry(&passwd, user)
+}
+
+/// Parses the `passwd` contents to find the uid & home directory of `user`
+#[cfg(unix)]
+fn parse_passwd_entry(passwd: &str, user: &str) -> Option<(u32, std::path::PathBuf)> {
+    passwd.lines().find_map(|line| {
+        // each line is of the form name:password:uid:gid:gecos:home:shell
+        let fields: Vec<&str> = line.split(':').collect();
+        if fields.len() >= 7 && fields[0] == user {
+            let uid = fields[2].parse().ok()?;
+            Some((uid, std::path::PathBuf::from(fields[5])))
+        } else {
+            None
+        }
+    })
+}
+
+/// Parses the `passwd` contents to find the name of the user with `uid`
+#[cfg(unix)]
+fn parse_passwd_user(passwd: &str, uid: u32) -> Option<String> {
+    passwd.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        if fields.len() >= 7 && fields[2].parse() == Ok(uid) {
+            Some(fields[0].to_owned())
+        } else {
+            None
+        }
+    })
+}
+
+/// Set up the env vars required to connect to the graphical session of the user with `uid`, as
+/// per logind. Env vars which have been explicitly set on `cmd` are left as is.
+#[cfg(unix)]
+fn configure_session_env(cmd: &mut Command, uid: u32) {
+    let is_set = |cmd: &Command, var: &str| cmd.get_envs().any(|(key, _)| key == var);
+    let runtime_dir = std::path::PathBuf::from(format!("/run/user/{uid}"));
+
+    if !is_set(cmd, "DISPLAY") {
+        if let Some(display) = get_session_display(uid) {
+            cmd.env("DISPLAY", display);
+        }
+    }
+    if !is_set(cmd, "WAYLAND_DISPLAY") {
+        // the compositor's socket is of the form wayland-N, alongside a wayland-N.lock file
+        let socket = std::fs::read_dir(&runtime_dir).ok().and_then(|entries| {
+            entries
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                .find(|name| name.starts_with("wayland-") && !name.ends_with(".lock"))
+        });
+        if let Some(socket) = socket {
+            cmd.env("WAYLAND_DISPLAY", socket);
+        }
+    }
+    if !is_set(cmd, "XAUTHORITY") {
+        let home = std::fs::read_to_string("/etc/passwd")
+            .ok()
+            .and_then(|passwd| parse_passwd_user(&passwd, uid))
+            .and_then(|user| get_user_entry(&user))
+            .map(|(_, home)| home);
+        let xauthority = [
+            Some(runtime_dir.join("gdm/Xauthority")),
+            home.map(|home| home.join(".Xauthority")),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|pb| pb.is_file());
+        if let Some(xauthority) = xauthority {
+            cmd.env("XAUTHORITY", xauthority);
+        }
+    }
+    if !is_set(cmd, "DBUS_SESSION_BUS_ADDRESS") && runtime_dir.join("bus").exists() {
+        cmd.env(
+            "DBUS_SESSION_BUS_ADDRESS",
+            format!("unix:path={}", runtime_dir.join("bus").display()),
+        );
+    }
+}
+
+/// Returns the X11 display of the graphical session of the user with `uid`, via `loginctl`
+#[cfg(unix)]
+fn get_session_display(uid: u32) -> Option<String> {
+    let loginctl = |args: &[&str]| {
+        let output = Command::new("loginctl")
+            .args(args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    // the Display property of a user is the id of its graphical session
+    let user_info = loginctl(&["show-user", &uid.to_string(), "--property=Display"])?;
+    let session = parse_loginctl_property(&user_info, "Display")?;
+    let session_info = loginctl(&["show-session", &session, "--property=Display"])?;
+    parse_loginctl_property(&session_info, "Display")
+}
+
+/// Returns the value of the property `name` in `output` of `loginctl show-*`
+#[cfg(unix)]
+fn parse_loginctl_property(output: &str, name: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        if key == name && !value.trim().is_empty() {
+            Some(value.trim().to_owned())
+        } else {
+            None
+        }
+    })
+}
+
+/// Returns a command which runs `cmd` as `user` via `sudo`, preserving the env vars
+/// required to connect to the user's display
+#[cfg(unix)]
+pub(crate) fn deescalate_command(cmd: &Command, user: &str) -> Command {
+    let mut preserved_env = vec![
+        "DISPLAY",
+        "WAYLAND_DISPLAY",
+        "XAUTHORITY",
+        "DESKTOP_STARTUP_ID",
+    ];
+    for var in ["TZ", "DBUS_SESSION_BUS_ADDRESS"] {
+        if cmd
+            .get_envs()
+            .any(|(key, value)| key == var && value.is_some())
+        {
+            preserved_env.push(var);
+        }
+    }
+    let mut runtime_dir = None;
+    if let Some((uid, _)) = get_user_entry(user) {
+        // wayland needs the user's runtime dir, and not that of root
+        let dir = std::path::PathBuf::from(format!("/run/user/{uid}"));
+        if dir.is_dir() {
+            preserved_env.push("XDG_RUNTIME_DIR");
+            runtime_dir = Some(dir);
+        }
+    }
+    let prefix = [
+        "sudo".to_owned(),
+        "-u".to_owned(),
+        user.to_owned(),
+        "-H".to_owned(),
+        format!("--preserve-env={}", preserved_env.join(",")),
+        "--".to_owned(),
+    ];
+    let mut deescalated = prefix_command(&prefix, cmd);
+    if let Some(dir) = runtime_dir {
+        deescalated.env("XDG_RUNTIME_DIR", dir);
+    }
+    log::debug!("deescalated command to user {}: {:?}", user, &deescalated);
+    deescalated
+}
+
+/// Returns a command which runs `cmd` via `prefix`, e.g. `firefox <url>` with a prefix of
+/// `["sudo", "-u", "user"]` becomes `sudo -u user firefox <url>`. The env vars and current
+/// dir of `cmd` are carried over.
+pub(crate) fn prefix_command(prefix: &[String], cmd: &Command) -> Command {
+    let mut prefixed = Command::new(&prefix[0]);
+    prefixed
+        .args(&prefix[1..])
+        .arg(cmd.get_program())
+        .args(cmd.get_args());
+    copy_env_and_dir(cmd, &mut prefixed);
+    prefixed
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    fn get_env<'a>(cmd: &'a Command, key: &str) -> Option<&'a OsStr> {
+        cmd.get_envs().find(|(k, _)| *k == key).and_then(|(_, v)| v)
+    }
+
+    #[test]
+    fn test_display_env() {
+        let mut cmd = Command::new("true");
+        configure_env(&mut cmd, BrowserOptions::new().with_display(":1"));
+        assert_eq!(get_env(&cmd, "DISPLAY"), Some(OsStr::new(":1")));
+
+        let mut cmd = Command::new("true");
+        configure_env(&mut cmd, BrowserOptions::new().with_display("wayland-1"));
+        assert_eq!(
+            get_env(&cmd, "WAYLAND_DISPLAY"),
+            Some(OsStr::new("wayland-1"))
+        );
+        assert_eq!(get_env(&cmd, "DISPLAY"), None);
+    }
+
+    #[test]
+    fn test_timezone_env() {
+        let mut cmd = Command::new("firefox");
+        configure_env(
+            &mut cmd,
+            BrowserOptions::new().with_timezone("Asia/Kolkata"),
+        );
+        assert_eq!(get_env(&cmd, "TZ"), Some(OsStr::new("Asia/Kolkata")));
+
+        let cmd = deescalate_command(&cmd, "webbrowser-test-no-such-user");
+        let preserve_env = cmd
+            .get_args()
+            .find_map(|arg| arg.to_str()?.strip_prefix("--preserve-env="))
+            .expect("env not preserved");
+        assert!(preserve_env.split(',').any(|var| var == "TZ"));
+    }
+
+    #[test]
+    fn test_systemd_scope() {
+        let mut options = BrowserOptions::new();
+        options
+            .with_systemd_scope(&[("MemoryMax", "1G"), ("CPUQuota", "50%")])
+            .with_dry_run(true);
+        let mut cmd = Command::new("/usr/bin/firefox");
+        cmd.arg("https://example.com");
+        run_command(&mut cmd, true, &options).expect("dry run failed");
+        let has_systemd_run = std::env::var_os("PATH").map_or(false, |paths| {
+            std::env::split_paths(&paths).any(|dir| dir.join("systemd-run").is_file())
+        });
+        let args: Vec<_> = cmd.get_args().collect();
+        if has_systemd_run {
+            assert!(Path::new(cmd.get_program()).ends_with("systemd-run"));
+            assert_eq!(
+                args,
+                [
+                    "--user",
+                    "--scope",
+                    "--quiet",
+                    "-p",
+                    "MemoryMax=1G",
+                    "-p",
+                    "CPUQuota=50%",
+                    "--",
+                    "/usr/bin/firefox",
+                    "https://example.com"
+                ]
+            );
+        } else {
+            // we fall back to launching without a scope
+            assert_eq!(cmd.get_program(), "/usr/bin/firefox");
+            assert_eq!(args, ["https://example.com"]);
+        }
+
+        // text browsers aren't launched in a scope
+        let mut cmd = Command::new("/usr/bin/lynx");
+        run_command(&mut cmd, false, &options).expect("dry run failed");
+        assert_eq!(cmd.get_program(), "/usr/bin/lynx");
+    }
+
+    #[test]
+    fn test_check_early_exit() {
+        let spawn = |script: &str| {
+            Command::new("/bin/sh")
+                .args(["-c", script])
+                .spawn()
+                .expect("failed to spawn")
+        };
+        let grace_period = Duration::from_secs(2);
+
+        // a browser handing over to an existing instance exits immediately with 0
+        let mut child = spawn("exit 0");
+        assert!(check_early_exit(&mut child, grace_period).is_ok());
+
+        let mut child = spawn("exit 3");
+        assert!(check_early_exit(&mut child, grace_period).is_err());
+
+        // a browser which keeps running is a success too
+        let mut child = spawn("sleep 5");
+        assert!(check_early_exit(&mut child, Duration::from_millis(50)).is_ok());
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn test_verify_launch() {
+        // launches aren't watched by default
+        let mut options = BrowserOptions::new();
+        let mut cmd = Command::new("/bin/sh");
+        cmd.args(["-c", "exit 1"]);
+        assert!(run_command(&mut cmd, true, &options).is_ok());
+
+        options.with_verify_launch(true);
+        let mut cmd = Command::new("/bin/sh");
+        cmd.args(["-c", "exit 1"]);
+        let err = run_command(&mut cmd, true, &options).expect_err("failed launch not detected");
+        assert_eq!(err.kind(), ErrorKind::Other);
+
+        // handing over to a running instance doesn't trigger the fallbacks
+        let mut cmd = Command::new("/bin/sh");
+        cmd.args(["-c", "exit 0"]);
+        let pid = run_command(&mut cmd, true, &options).expect("quick exit 0 not a success");
+        assert!(pid.is_some());
+    }
+
+    #[test]
+    fn test_parse_passwd_entry() {
+        let passwd = "root:x:0:0:root:/root:/bin/bash\n\
+            # comment\n\
+            alice:x:1000:1000:Alice,,,:/home/alice:/bin/zsh\n";
+        assert_eq!(
+            parse_passwd_entry(passwd, "alice"),
+            Some((1000, std::path::PathBuf::from("/home/alice")))
+        );
+        assert_eq!(parse_passwd_entry(passwd, "bob"), None);
+    }
+
+    #[test]
+    fn test_parse_passwd_user() {
+        let passwd = "root:x:0:0:root:/root:/bin/bash\n\
+            alice:x:1000:1000:Alice,,,:/home/alice:/bin/zsh\n";
+        assert_eq!(parse_passwd_user(passwd, 1000).as_deref(), Some("alice"));
+        assert_eq!(parse_passwd_user(passwd, 1001), None);
+    }
+
+    #[test]
+    fn test_parse_loginctl_property() {
+        assert_eq!(
+            parse_loginctl_property("Display=:1\nType=x11\n", "Display").as_deref(),
+            Some(":1")
+        );
+        assert_eq!(parse_loginctl_property("Display=\n", "Display"), None);
+        assert_eq!(parse_loginctl_property("Type=x11\n", "Display"), None);
+    }
+
+    #[test]
+    fn test_deescalate_command() {
+        let mut cmd = Command::new("firefox");
+        cmd.arg("https://example.com").env("DISPLAY", ":1");
+        let cmd = deescalate_command(&cmd, "webbrowser-test-no-such-user");
+        assert_eq!(cmd.get_program(), "sudo");
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(
+            args,
+            [
+                "-u",
+                "webbrowser-test-no-such-user",
+                "-H",
+                "--preserve-env=DISPLAY,WAYLAND_DISPLAY,XAUTHORITY,DESKTOP_STARTUP_ID",
+                "--",
+                "firefox",
+                "https://example.com"
+            ]
+        );
+        assert_eq!(get_env(&cmd, "DISPLAY"), Some(OsStr::new(":1")));
+    }
+
+    #[test]
+    fn test_new_window_arg() {
+        let mut options = BrowserOptions::new();
+        options.with_new_window(true);
+        for (program, expected) in [
+            (
+                "/usr/lib/firefox/firefox",
+                vec!["-new-window", "https://example.com"],
+            ),
+            (
+                "/usr/bin/google-chrome-stable",
+                vec!["--new-window", "https://example.com"],
+            ),
+            ("/usr/bin/xdg-open", vec!["https://example.com"]),
+        ] {
+            let mut cmd = Command::new(program);
+            cmd.arg("https://example.com");
+            configure_args(&mut cmd, &options);
+            assert_eq!(cmd.get_program(), program);
+            assert_eq!(cmd.get_args().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_incognito_arg() {
+        let mut options = BrowserOptions::new();
+        options.with_incognito(true);
+        options.with_new_window(true);
+        for (program, expected) in [
+            (
+                "/usr/lib/firefox/firefox",
+                vec!["-private-window", "https://example.com"],
+            ),
+            (
+                "/usr/bin/brave-browser",
+                vec!["--new-window", "--incognito", "https://example.com"],
+            ),
+            ("/usr/bin/epiphany", vec!["https://example.com"]),
+        ] {
+            let mut cmd = Command::new(program);
+            cmd.arg("https://example.com");
+            configure_args(&mut cmd, &options);
+            assert_eq!(cmd.get_args().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_fullscreen_and_maximized() {
+        let args_for = |program: &str, fullscreen: bool, maximized: bool| {
+            let mut options = BrowserOptions::new();
+            options
+                .with_fullscreen(fullscreen)
+                .with_maximized(maximized);
+            let mut cmd = Command::new(program);
+            cmd.arg("https://example.com");
+            configure_args(&mut cmd, &options);
+            cmd.get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+        for (program, fullscreen, maximized, expected) in [
+            ("/usr/bin/chromium", true, false, vec!["--start-fullscreen"]),
+            ("/usr/bin/chromium", false, true, vec!["--start-maximized"]),
+            // fullscreen takes precedence
+            ("/usr/bin/chromium", true, true, vec!["--start-fullscreen"]),
+            ("/usr/bin/firefox", true, true, vec!["--kiosk"]),
+            ("/usr/bin/firefox", false, true, vec![]),
+            ("/usr/bin/epiphany", true, false, vec![]),
+        ] {
+            let mut expected = expected;
+            expected.push("https://example.com");
+            assert_eq!(args_for(program, fullscreen, maximized), expected);
+        }
+    }
+
+    #[test]
+    fn test_window_position() {
+        let args_for = |program: &str, maximized: bool| {
+            let mut options = BrowserOptions::new();
+            options
+                .with_window_position(-10, 20, 800, 600)
+                .with_maximized(maximized);
+            let mut cmd = Command::new(program);
+            cmd.arg("https://example.com");
+            configure_args(&mut cmd, &options);
+            cmd.get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            args_for("/usr/bin/chromium", false),
+            [
+                "--window-position=-10,20",
+                "--window-size=800,600",
+                "https://example.com"
+            ]
+        );
+        assert_eq!(
+            args_for("/usr/bin/firefox", false),
+            ["-width", "800", "-height", "600", "https://example.com"]
+        );
+        assert_eq!(
+            args_for("/usr/bin/epiphany", false),
+            ["https://example.com"]
+        );
+
+        // maximized takes precedence
+        assert_eq!(
+            args_for("/usr/bin/chromium", true),
+            ["--start-maximized", "https://example.com"]
+        );
+        assert_eq!(args_for("/usr/bin/firefox", true), ["https://example.com"]);
+    }
+
+    #[test]
+    fn test_display_platform() {
+        use crate::DisplayPlatform;
+        let mut options = BrowserOptions::new();
+        for (platform, chromium_arg, firefox_env) in [
+            (
+                DisplayPlatform::Wayland,
+                "--ozone-platform=wayland",
+                [("MOZ_ENABLE_WAYLAND", Some("1")), ("GDK_BACKEND", None)],
+            ),
+            (
+                DisplayPlatform::X11,
+                "--ozone-platform=x11",
+                [
+                    ("MOZ_ENABLE_WAYLAND", Some("0")),
+                    ("GDK_BACKEND", Some("x11")),
+                ],
+            ),
+        ] {
+            options.with_display_platform(platform);
+            let mut cmd = Command::new("/usr/bin/chromium");
+            cmd.arg("https://example.com");
+            configure_args(&mut cmd, &options);
+            assert_eq!(
+                cmd.get_args().collect::<Vec<_>>(),
+                [chromium_arg, "https://example.com"]
+            );
+            assert_eq!(get_env(&cmd, "MOZ_ENABLE_WAYLAND"), None);
+
+            let mut cmd = Command::new("/usr/bin/firefox");
+            cmd.arg("https://example.com");
+            configure_args(&mut cmd, &options);
+            assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["https://example.com"]);
+            for (name, value) in firefox_env {
+                assert_eq!(get_env(&cmd, name), value.map(OsStr::new));
+            }
+        }
+    }
+
+    #[test]
+    fn test_disable_gpu() {
+        let mut options = BrowserOptions::new();
+        options.with_disable_gpu(true);
+        for (program, expected) in [
+            (
+                "/usr/bin/chromium-browser",
+                vec![
+                    "--disable-gpu",
+                    "--disable-software-rasterizer",
+                    "https://example.com",
+                ],
+            ),
+            ("/usr/bin/firefox", vec!["https://example.com"]),
+        ] {
+            let mut cmd = Command::new(program);
+            cmd.arg("https://example.com");
+            configure_args(&mut cmd, &options);
+            assert_eq!(cmd.get_args().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_profile() {
+        let mut options = BrowserOptions::new();
+        options.with_profile("Profile 1");
+        for (program, expected) in [
+            (
+                "/usr/bin/google-chrome",
+                vec!["--profile-directory=Profile 1", "https://example.com"],
+            ),
+            (
+                "/usr/bin/firefox",
+                vec!["-P", "Profile 1", "https://example.com"],
+            ),
+            ("/usr/bin/epiphany", vec!["https://example.com"]),
+        ] {
+            let mut cmd = Command::new(program);
+            cmd.arg("https://example.com");
+            configure_args(&mut cmd, &options);
+            assert_eq!(cmd.get_args().collect::<Vec<_>>(), expected);
+        }
+
+        options.with_profile(" ");
+        assert!(options.validate().is_err());
+    }
+
+    #[test]
+    fn test_extra_args() {
+        let mut options = BrowserOptions::new();
+        options
+            .with_args(&[
+                "--app=https://example.com/?a=1&b=2",
+                "--window-size=800,600",
+            ])
+            .with_new_window(true);
+        let mut cmd = Command::new("/usr/bin/chromium");
+        cmd.arg("https://example.com");
+        configure_args(&mut cmd, &options);
+        assert_eq!(
+            cmd.get_args().collect::<Vec<_>>(),
+            [
+                "--new-window",
+                "--app=https://example.com/?a=1&b=2",
+                "--window-size=800,600",
+                "https://example.com"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_kiosk() {
+        let mut options = BrowserOptions::new();
+        options.with_kiosk(true).with_fullscreen(true);
+        for (program, expected) in [
+            ("/usr/bin/chromium", vec!["--kiosk", "https://example.com"]),
+            ("/usr/bin/firefox", vec!["--kiosk", "https://example.com"]),
+            ("/usr/bin/epiphany", vec!["https://example.com"]),
+        ] {
+            let mut cmd = Command::new(program);
+            cmd.arg("https://example.com");
+            configure_args(&mut cmd, &options);
+            assert_eq!(cmd.get_args().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    /// Configures firefox as per `options`, returning its args, and the prefs in the temporary
+    /// profile it gets run with, checking that the profile is private, and removed once dropped
+    fn firefox_profile_prefs(options: &BrowserOptions) -> (Vec<String>, String) {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut cmd = Command::new("/usr/bin/firefox");
+        cmd.arg("https://example.com");
+        configure_args(&mut cmd, options);
+        let profile = configure_firefox_profile(&mut cmd, options)
+            .expect("failed to configure profile")
+            .expect("no temporary profile");
+        let args: Vec<String> = cmd
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(args[0], "-profile");
+        assert_eq!(Path::new(&args[1]), profile.path());
+        assert_eq!(args.last().map(String::as_str), Some("https://example.com"));
+
+        let path = profile.path().to_owned();
+        let mode = std::fs::metadata(&path)
+            .expect("no profile")
+            .permissions()
+            .mode();
+        let prefs = std::fs::read_to_string(path.join("user.js")).expect("prefs not written");
+        drop(profile);
+        assert_eq!(mode & 0o777, 0o700);
+        assert!(!path.exists(), "profile not removed");
+        (args, prefs)
+    }
+
+    #[test]
+    fn test_temporary_firefox_profile() {
+        let mut options = BrowserOptions::new();
+        options.with_suppress_first_run(true);
+
+        // each launch gets a fresh profile
+        let (first, _) = firefox_profile_prefs(&options);
+        let (second, _) = firefox_profile_prefs(&options);
+        assert_ne!(first[1], second[1]);
+
+        // nothing is created for dry runs
+        options.with_dry_run(true);
+        let mut cmd = Command::new("/usr/bin/firefox");
+        let profile = configure_firefox_profile(&mut cmd, &options).expect("failed to configure");
+        assert!(profile.is_none());
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(args[0], "-profile");
+        assert!(!Path::new(args[1]).exists());
+
+        // a named profile can't be used along with it, though it's fine for chromium
+        options.with_profile("Work");
+        let mut cmd = Command::new("/usr/bin/firefox");
+        configure_args(&mut cmd, &options);
+        assert_eq!(
+            configure_firefox_profile(&mut cmd, &options)
+                .expect_err("named profile accepted")
+                .kind(),
+            ErrorKind::InvalidInput
+        );
+        let mut cmd = Command::new("/usr/bin/chromium");
+        assert!(run_command(&mut cmd, true, &options).is_ok());
+    }
+
+    #[test]
+    fn test_accept_languages() {
+        let mut options = BrowserOptions::new();
+        options.with_accept_languages(&["en-US", "en", "fr"]);
+        assert!(options.validate().is_ok());
+        let mut cmd = Command::new("/usr/bin/chromium");
+        cmd.arg("https://example.com");
+        configure_args(&mut cmd, &options);
+        assert_eq!(
+            cmd.get_args().collect::<Vec<_>>(),
+            ["--accept-lang=en-US,en,fr", "https://example.com"]
+        );
+
+        assert_eq!(
+            firefox_profile_prefs(&options).1,
+            "user_pref(\"intl.accept_languages\", \"en-US,en,fr\");\n"
+        );
+
+        options.with_accept_languages(&["en\", \"x"]);
+        assert!(options.validate().is_err());
+    }
+
+    #[test]
+    fn test_ignore_cert_errors() {
+        let mut options = BrowserOptions::new();
+        options.with_ignore_cert_errors(true);
+        let mut cmd = Command::new("/usr/bin/google-chrome");
+        cmd.arg("https://example.com");
+        configure_args(&mut cmd, &options);
+        assert_eq!(
+            cmd.get_args().collect::<Vec<_>>(),
+            ["--ignore-certificate-errors", "https://example.com"]
+        );
+
+        assert_eq!(firefox_profile_prefs(&options).1, INSECURE_FIREFOX_PREFS);
+
+        let mut cmd = Command::new("/usr/bin/epiphany");
+        cmd.arg("https://example.com");
+        configure_args(&mut cmd, &options);
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["https://example.com"]);
+    }
+
+    #[test]
+    fn test_remote_debugging_port() {
+        let mut options = BrowserOptions::new();
+        options.remote_debugging_port = Some(9222);
+        for (program, expected) in [
+            (
+                "/usr/bin/chromium",
+                vec!["--remote-debugging-port=9222", "https://example.com"],
+            ),
+            ("/usr/bin/firefox", vec!["https://example.com"]),
+        ] {
+            let mut cmd = Command::new(program);
+            cmd.arg("https://example.com");
+            configure_args(&mut cmd, &options);
+            assert_eq!(cmd.get_args().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_suppress_first_run() {
+        let mut options = BrowserOptions::new();
+        options.with_suppress_first_run(true);
+        let mut cmd = Command::new("/usr/bin/chromium");
+        cmd.arg("https://example.com");
+        configure_args(&mut cmd, &options);
+        assert_eq!(
+            cmd.get_args().collect::<Vec<_>>(),
+            [
+                "--no-first-run",
+                "--no-default-browser-check",
+                "https://example.com"
+            ]
+        );
+
+        // prefs are combined, if more than one option needs a firefox profile
+        options.with_ignore_cert_errors(true);
+        assert_eq!(
+            firefox_profile_prefs(&options).1,
+            format!("{INSECURE_FIREFOX_PREFS}{FIRST_RUN_FIREFOX_PREFS}")
+        );
+
+        let mut cmd = Command::new("/usr/bin/lynx");
+        cmd.arg("https://example.com");
+        configure_args(&mut cmd, &options);
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["https://example.com"]);
+    }
+
+    #[test]
+    fn test_force_accessibility() {
+        let mut options = BrowserOptions::new();
+        options.with_force_accessibility(true);
+        for (program, expected) in [
+            (
+                "/usr/bin/chromium",
+                vec!["--force-renderer-accessibility", "https://example.com"],
+            ),
+            ("/usr/bin/firefox", vec!["https://example.com"]),
+        ] {
+            let mut cmd = Command::new(program);
+            cmd.arg("https://example.com");
+            configure_args(&mut cmd, &options);
+            assert_eq!(cmd.get_args().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_cache_dir() {
+        let cache_dir = std::env::temp_dir().join(format!(
+            "webbrowser-test-cache.{}/nested",
+            std::process::id()
+        ));
+        let mut options = BrowserOptions::new();
+        options.with_cache_dir(&cache_dir);
+        let validated = options.validate();
+        let created_by_validation = cache_dir.exists();
+
+        // the dir is created only when actually launching a chromium based browser
+        options.with_dry_run(true);
+        let mut cmd = Command::new("google-chrome");
+        run_command(&mut cmd, true, &options).expect("dry run failed");
+        let created_by_dry_run = cache_dir.exists();
+        options.with_dry_run(false);
+        let mut cmd = Command::new("/bin/sh");
+        cmd.args(["-c", "exit 0"]);
+        run_command(&mut cmd, true, &options).expect("failed to run");
+        let created_for_other = cache_dir.exists();
+        let mut cmd = Command::new("/nonexistent/google-chrome");
+        let launched = run_command(&mut cmd, true, &options);
+        let created = cache_dir.is_dir();
+
+        // a file can't be an ancestor
+        let file = cache_dir.with_file_name("file");
+        std::fs::write(&file, "").expect("failed to create file");
+        let invalid = BrowserOptions::new()
+            .with_cache_dir(&file.join("cache"))
+            .validate();
+        let _ = std::fs::remove_dir_all(cache_dir.parent().expect("no parent"));
+
+        assert!(validated.is_ok());
+        assert!(!created_by_validation);
+        assert!(!created_by_dry_run);
+        assert!(!created_for_other);
+        assert!(launched.is_err());
+        assert!(created);
+        assert_eq!(
+            invalid.expect_err("file accepted as cache dir").kind(),
+            ErrorKind::InvalidInput
+        );
+
+        let mut arg = OsString::from("--disk-cache-dir=");
+        arg.push(&cache_dir);
+        for (program, expected) in [
+            (
+                "/usr/bin/google-chrome",
+                vec![arg.as_os_str(), "https://example.com".as_ref()],
+            ),
+            ("/usr/bin/firefox", vec!["https://example.com".as_ref()]),
+        ] {
+            let mut cmd = Command::new(program);
+            cmd.arg("https://example.com");
+            configure_args(&mut cmd, &options);
+            assert_eq!(cmd.get_args().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_wait_for_exit() {
+        let mut options = BrowserOptions::new();
+        options.with_wait_for_exit(true);
+        let start = Instant::now();
+        let mut cmd = Command::new("/bin/sh");
+        cmd.args(["-c", "sleep 0.3"]);
+        let pid = run_command(&mut cmd, true, &options).expect("failed to run");
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert_eq!(pid, None);
+
+        let mut cmd = Command::new("/bin/sh");
+        cmd.args(["-c", "sleep 0.2; echo noise; exit 3"]);
+        options.with_suppress_output(true);
+        let err = run_command(&mut cmd, true, &options).expect_err("exit code not reported");
+        assert_eq!(
+            err.to_string(),
+            "browser exited unsuccessfully (exit status: 3)"
+        );
+    }
+
+    #[test]
+    fn test_command_hook() {
+        // the hook is global, so it only touches the commands of this test
+        crate::set_command_hook(Box::new(|cmd| {
+            if cmd.get_program() == "/usr/bin/hooked-browser" {
+                cmd.arg("--hooked").env("HOOKED", "1");
+            }
+        }));
+        let mut options = BrowserOptions::new();
+        options.with_dry_run(true);
+        let mut cmd = Command::new("/usr/bin/hooked-browser");
+        cmd.arg("https://example.com");
+        run_command(&mut cmd, true, &options).expect("failed to run");
+        assert_eq!(
+            cmd.get_args().collect::<Vec<_>>(),
+            ["https://example.com", "--hooked"]
+        );
+        assert_eq!(get_env(&cmd, "HOOKED"), Some(OsStr::new("1")));
+    }
+
+    #[test]
+    fn test_retain_child() {
+        let options = BrowserOptions::new();
+        let (pid, child) = crate::with_child_capture(|| {
+            let mut cmd = Command::new("/bin/sh");
+            cmd.args(["-c", "sleep 5"]);
+            run_command(&mut cmd, true, &options).expect("failed to run")
+        });
+        let mut child = child.expect("child not retained");
+        assert_eq!(pid, Some(child.id()));
+        child.kill().expect("failed to kill");
+        assert!(!child.wait().expect("failed to wait").success());
+
+        // children aren't retained unless being captured
+        let mut cmd = Command::new("/bin/sh");
+        cmd.args(["-c", "exit 0"]);
+        run_command(&mut cmd, true, &options).expect("failed to run");
+        assert!(crate::CHILD.with(|child| child.borrow().is_none()));
+    }
+
+    #[test]
+    fn test_no_path_search() {
+        let mut options = BrowserOptions::new();
+        options
+            .with_no_path_search(true)
+            .with_systemd_scope(&[])
+            .with_dry_run(true);
+        let mut cmd = Command::new("/usr/bin/firefox");
+        run_command(&mut cmd, true, &options).expect("absolute command rejected");
+        assert_eq!(cmd.get_program(), "/usr/bin/firefox");
+
+        let mut cmd = Command::new("firefox");
+        assert!(run_command(&mut cmd, true, &options).is_err());
+
+        options.with_wrapper_command("sso-open");
+        let mut cmd = Command::new("/usr/bin/firefox");
+        assert!(run_command(&mut cmd, true, &options).is_err());
+    }
+
+    #[test]
+    fn test_clean_env() {
+        let mut options = BrowserOptions::new();
+        options.with_clean_env(true).with_display(":2");
+        let mut cmd = Command::new("firefox");
+        cmd.env("LD_PRELOAD", "/tmp/contaminated.so");
+        configure_env(&mut cmd, &options);
+        assert_eq!(get_env(&cmd, "LD_PRELOAD"), None);
+        assert_eq!(get_env(&cmd, "PATH"), std::env::var_os("PATH").as_deref());
+        assert_eq!(get_env(&cmd, "DISPLAY"), Some(OsStr::new(":2")));
+    }
+
+    #[test]
+    fn test_clean_env_survives_rewrites() {
+        let dir =
+            std::env::temp_dir().join(format!("webbrowser-test-clean-env.{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("failed to create dir");
+        let firefox = dir.join("firefox");
+        std::fs::write(&firefox, "#!/bin/sh\nenv > \"$0.env\"\n").expect("failed to create script");
+        std::fs::set_permissions(
+            &firefox,
+            std::os::unix::fs::PermissionsExt::from_mode(0o755),
+        )
+        .expect("failed to set permissions");
+
+        // incognito rewrites the command with the extra arg, as do the wrapper, sudo etc.
+        let mut options = BrowserOptions::new();
+        options
+            .with_clean_env(true)
+            .with_incognito(true)
+            .with_dry_run(true);
+        let mut cmd = Command::new(&firefox);
+        cmd.env("LD_PRELOAD", "/tmp/contaminated.so")
+            .arg("https://example.com");
+        run_command(&mut cmd, true, &options).expect("dry run failed");
+        assert_eq!(cmd.get_args().next(), Some(OsStr::new("-private-window")));
+
+        // run what would have been run, to see the env the browser gets
+        let status = cmd.status().expect("failed to run script");
+        let env = std::fs::read_to_string(dir.join("firefox.env"));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(status.success());
+        let env = env.expect("script didn't run");
+        let has_var = |var: &str| env.lines().any(|line| line.starts_with(&format!("{var}=")));
+        assert!(!has_var("LD_PRELOAD"));
+        assert!(has_var("PATH"));
+        // cargo sets this for us, and it isn't one of the retained vars
+        if std::env::var_os("CARGO_MANIFEST_DIR").is_some() {
+            assert!(!has_var("CARGO_MANIFEST_DIR"));
+        }
+    }
+
+    #[test]
+    fn test_session_env() {
+        let env_var = |var: &str| match var {
+            "DISPLAY" => Some(OsString::from(":0")),
+            "WAYLAND_DISPLAY" => Some(OsString::from("wayland-0")),
+            "DBUS_SESSION_BUS_ADDRESS" => Some(OsString::from("unix:path=/run/user/1000/bus")),
+            _ => None,
+        };
+        let mut options = BrowserOptions::new();
+        let mut cmd = Command::new("xdg-open");
+        forward_session_env(&mut cmd, &options, env_var);
+        assert_eq!(get_env(&cmd, "DISPLAY"), Some(OsStr::new(":0")));
+        assert_eq!(
+            get_env(&cmd, "WAYLAND_DISPLAY"),
+            Some(OsStr::new("wayland-0"))
+        );
+        assert_eq!(
+            get_env(&cmd, "DBUS_SESSION_BUS_ADDRESS"),
+            Some(OsStr::new("unix:path=/run/user/1000/bus"))
+        );
+
+        // they survive a clean env, and explicitly set ones are left as is
+        options.with_clean_env(true);
+        let mut cmd = Command::new("xdg-open");
+        cmd.env_clear().env("DISPLAY", ":1");
+        forward_session_env(&mut cmd, &options, env_var);
+        assert_eq!(get_env(&cmd, "DISPLAY"), Some(OsStr::new(":1")));
+        assert_eq!(
+            get_env(&cmd, "WAYLAND_DISPLAY"),
+            Some(OsStr::new("wayland-0"))
+        );
+
+        // a specified display isn't overridden
+        options.with_display("wayland-1");
+        let mut cmd = Command::new("xdg-open");
+        forward_session_env(&mut cmd, &options, env_var);
+        assert_eq!(get_env(&cmd, "DISPLAY"), None);
+        assert_eq!(get_env(&cmd, "WAYLAND_DISPLAY"), None);
+        assert!(get_env(&cmd, "DBUS_SESSION_BUS_ADDRESS").is_some());
+
+        // our session isn't that of another user
+        let mut cmd = Command::new("xdg-open");
+        forward_session_env(
+            &mut cmd,
+            BrowserOptions::new().with_deescalate(true),
+            env_var,
+        );
+        assert_eq!(cmd.get_envs().count(), 0);
+    }
+
+    #[test]
+    fn test_wrapper_command() {
+        let mut options = BrowserOptions::new();
+        options
+            .with_wrapper_command(r#"sso-open --realm "corp realm""#)
+            .with_dry_run(true);
+        let mut cmd = Command::new("/usr/bin/firefox");
+        cmd.arg("https://example.com");
+        run_command(&mut cmd, true, &options).expect("dry run failed");
+        assert_eq!(cmd.get_program(), "sso-open");
+        assert_eq!(
+            cmd.get_args().collect::<Vec<_>>(),
+            [
+                "--realm",
+                "corp realm",
+                "/usr/bin/firefox",
+                "https://example.com"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_startup_notify() {
+        let prefix = format!("webbrowser-{}-{}-", std::process::id(), get_hostname());
+        let mut options = BrowserOptions::new();
+        options.with_dry_run(true);
+
+        let mut cmd = Command::new("/usr/bin/firefox");
+        run_command(&mut cmd, true, &options).expect("dry run failed");
+        let id = get_env(&cmd, "DESKTOP_STARTUP_ID")
+            .expect("startup id not set")
+            .to_string_lossy()
+            .into_owned();
+        assert!(id.starts_with(&format!("{prefix}firefox-")), "{}", id);
+
+        let mut cmd = Command::new("exo-open");
+        run_command(&mut cmd, true, &options).expect("dry run failed");
+        let id = get_env(&cmd, "DESKTOP_STARTUP_ID").expect("startup id not set");
+        assert_eq!(
+            cmd.get_args().collect::<Vec<_>>(),
+            [OsStr::new("--startup-id"), id]
+        );
+
+        // neither for text browsers, nor when disabled
+        let mut cmd = Command::new("lynx");
+        run_command(&mut cmd, false, &options).expect("dry run failed");
+        assert_eq!(get_env(&cmd, "DESKTOP_STARTUP_ID"), None);
+        options.with_startup_notify(false);
+        let mut cmd = Command::new("firefox");
+        run_command(&mut cmd, true, &options).expect("dry run failed");
+        assert_eq!(get_env(&cmd, "DESKTOP_STARTUP_ID"), None);
+    }
+}
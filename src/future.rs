@@ -0,0 +1,281 @@
+//! Support for opening a url, and getting a [Future] which resolves once the OS confirms (or
+//! refuses) the launch, for platforms which report this asynchronously.
+
+use crate::{open_browser_with_options, Browser, BrowserOptions, Result};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// Opens the specified URL in the default browser, returning a [Future] which resolves to the
+/// result of the launch.
+///
+/// Platform specific behaviour is as follows:
+/// * **iOS/tvOS/visionOS** - the future resolves when the completion handler of
+///   `openURL:options:completionHandler:` is called, i.e. it reports whether the url was actually
+///   opened. Note that the handler is called on the main thread, so the main run loop needs to be
+///   running for the future to resolve.
+/// * **macOS** - Launch Services reports the launch status synchronously, so the returned future
+///   is already resolved, with the same real success/failure signal.
+/// * **All other platforms** - the url is opened as with [open_browser_with_options], and the
+///   returned future is already resolved with its result.
+///
+/// If the `tokio` feature is enabled, and this is called from within a tokio runtime, the url is
+/// instead opened on the blocking thread pool via `spawn_blocking`, and the future resolves once
+/// that's done. This keeps blocking launches, like those of text browsers, off the runtime
+/// threads. This doesn't apply on iOS/tvOS/visionOS, where the url needs to be opened on the main
+/// thread, and on wasm.
+///
+/// The whole launch is moved to the blocking pool, rather than just spawning the browser via
+/// `tokio::process::Command`, as finding the browser blocks too, e.g. running helpers like
+/// `xdg-settings`, querying the registry, or calling D-Bus and Launch Services, and the command is
+/// rewritten in ways which `tokio::process::Command` doesn't support, e.g. for the wrapper
+/// command or the command hook.
+///
+/// This is available only with the `async` feature enabled.
+///
+/// # Examples
+/// ```no_run
+/// # async fn run() {
+/// if webbrowser::open_async("http://github.com").await.is_ok() {
+///     // ...
+/// }
+/// # }
+/// ```
+pub fn open_async(url: &str) -> OpenFuture {
+    open_browser_async(Browser::Default, url, &BrowserOptions::default())
+}
+
+/// Opens the specified URL on the specific browser (if available) requested, with the specified
+/// options, returning a [Future] which resolves to the result of the launch. See [open_async] for
+/// the platforms on which the result is reported asynchronously.
+///
+/// This is available only with the `async` feature enabled.
+///
+/// # Examples
+/// ```no_run
+/// use webbrowser::{open_browser_async, Browser, BrowserOptions};
+///
+/// # async fn run() {
+/// let result = open_browser_async(Browser::Default, "http://github.com", &BrowserOptions::new()).await;
+/// # }
+/// ```
+pub fn open_browser_async(browser: Browser, url: &str, options: &BrowserOptions) -> OpenFuture {
+    let completion = Completion::default();
+
+    #[cfg(all(
+        feature = "tokio",
+        not(any(
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "visionos",
+            target_family = "wasm"
+        ))
+    ))]
+    if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+        let future = OpenFuture {
+            state: completion.0.clone(),
+        };
+        let (url, options) = (url.to_owned(), options.clone());
+        runtime.spawn_blocking(move || {
+            completion.complete(open_browser_with_options(browser, &url, &options));
+        });
+        return future;
+    }
+
+    PENDING.with(|pending| *pending.borrow_mut() = Some(completion.clone()));
+    let result = open_browser_with_options(browser, url, options);
+    let deferred = PENDING
+        .with(|pending| pending.borrow_mut().take())
+        .is_none();
+
+    // an error is final, even if the completion had already been handed over to the OS
+    if result.is_err() || !deferred {
+        completion.complete(result);
+    }
+    OpenFuture {
+        state: completion.0,
+    }
+}
+
+/// Same as [open_browser_async], under the name mirroring [open_browser_with_options]
+///
+/// This is available only with the `async` feature enabled.
+///
+/// # Examples
+/// ```no_run
+/// use webbrowser::{open_browser_with_options_async, Browser, BrowserOptions};
+///
+/// # async fn run() {
+/// let options = BrowserOptions::new();
+/// let result = open_browser_with_options_async(Browser::Firefox, "http://github.com", &options).await;
+/// # }
+/// ```
+pub fn open_browser_with_options_async(
+    browser: Browser,
+    url: &str,
+    options: &BrowserOptions,
+) -> OpenFuture {
+    open_browser_async(browser, url, options)
+}
+
+/// The [Future] returned by [open_async] & [open_browser_async], resolving to the result of the
+/// launch
+#[derive(Debug)]
+pub struct OpenFuture {
+    state: Arc<Mutex<State>>,
+}
+
+impl Future for OpenFuture {
+    type Output = Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    completed: bool,
+    result: Option<Result<()>>,
+    waker: Option<Waker>,
+}
+
+/// The sending half of an [OpenFuture], to be completed once the result of the launch is known
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Completion(Arc<Mutex<State>>);
+
+impl Completion {
+    /// Resolves the future with `result`, unless it's already been resolved
+    pub(crate) fn complete(&self, result: Result<()>) {
+        let mut state = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        if state.completed {
+            return;
+        }
+        state.completed = true;
+        state.result = Some(result);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Takes over the completion of the [open_browser_async] call in progress on this thread, if
+/// any. Platforms which get the result of the launch asynchronously call this right before
+/// handing the url over to the OS, and complete it once the OS reports back.
+#[cfg_attr(
+    not(any(target_os = "ios", target_os = "tvos", target_os = "visionos")),
+    allow(dead_code)
+)]
+pub(crate) fn take_completion() -> Option<Completion> {
+    PENDING.with(|pending| pending.borrow_mut().take())
+}
+
+thread_local! {
+    /// The completion of the [open_browser_async] call in progress on this thread, if any
+    static PENDING: std::cell::RefCell<Option<Completion>> = const { std::cell::RefCell::new(None) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns dry run options which resolve firefox to a stub, so that urls open irrespective
+    /// of the host browsers, along with the dir holding the stub
+    #[cfg(all(
+        unix,
+        not(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "visionos",
+            target_os = "android",
+            target_family = "wasm",
+        ))
+    ))]
+    fn stub_firefox_options(name: &str) -> (BrowserOptions, std::path::PathBuf) {
+        let (dir, firefox) = crate::stub_executable(name);
+        let mut options = BrowserOptions::new();
+        options
+            .with_dry_run(true)
+            .with_binary_override(Browser::Firefox, &[&firefox.to_string_lossy()]);
+        (options, dir)
+    }
+
+    #[cfg(all(
+        unix,
+        not(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "visionos",
+            target_os = "android",
+            target_family = "wasm",
+        ))
+    ))]
+    #[tokio::test]
+    async fn test_open_async_resolves() {
+        let (options, dir) = stub_firefox_options("async-resolves");
+        let opened = open_browser_async(Browser::Firefox, "http://github.com", &options).await;
+        let invalid = open_browser_async(Browser::Firefox, "http://[::1", &options).await;
+        let aliased =
+            open_browser_with_options_async(Browser::Firefox, "http://github.com", &options).await;
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(opened.is_ok(), "dry run failed: {:?}", opened);
+        assert!(invalid.is_err(), "invalid url didn't fail");
+        assert!(aliased.is_ok(), "dry run failed: {:?}", aliased);
+    }
+
+    #[cfg(all(
+        unix,
+        not(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "visionos",
+            target_os = "android",
+            target_family = "wasm",
+        ))
+    ))]
+    #[test]
+    fn test_open_async_outside_runtime() {
+        // without a runtime to spawn on, the url gets opened right away
+        let (options, dir) = stub_firefox_options("async-outside-runtime");
+        let future = open_browser_async(Browser::Firefox, "http://github.com", &options);
+        let completed = future.state.lock().unwrap().completed;
+        let result = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("failed to build runtime")
+            .block_on(future);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(completed, "not opened right away");
+        assert!(result.is_ok(), "dry run failed: {:?}", result);
+    }
+
+    #[tokio::test]
+    async fn test_completion_resolves_later() {
+        let completion = Completion::default();
+        let future = OpenFuture {
+            state: completion.0.clone(),
+        };
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            completion.complete(Ok(()));
+            completion.complete(Err(std::io::Error::new(
+                crate::ErrorKind::Other,
+                "late failure",
+            )));
+        });
+        assert!(future.await.is_ok(), "first completion didn't win");
+        handle.join().unwrap();
+    }
+}
@@ -1,9 +1,15 @@
-use crate::{Browser, BrowserOptions, Error, ErrorKind, Result, TargetType};
+use crate::{
+    Browser, BrowserFamily, BrowserOptions, ErrorKind, HandlerKind, OpenOutcome, ResolvedTarget,
+    Result, TargetType,
+};
 use block2::Block;
+#[cfg(feature = "async")]
+use block2::RcBlock;
 use objc2::rc::Id;
 use objc2::runtime::Bool;
 use objc2::{class, msg_send, msg_send_id};
 use objc2_foundation::{NSDictionary, NSObject, NSString, NSURL};
+use std::io::Error;
 
 fn app() -> Option<Id<NSObject>> {
     unsafe { msg_send_id![class!(UIApplication), sharedApplication] }
@@ -18,6 +24,11 @@ fn open_url(
     unsafe { msg_send![app, openURL: url, options: options, completionHandler: handler] }
 }
 
+fn can_open_url(app: &NSObject, url: &NSURL) -> bool {
+    let result: Bool = unsafe { msg_send![app, canOpenURL: url] };
+    result.as_bool()
+}
+
 /// Deal with opening of browsers on iOS/tvOS/visionOS.
 ///
 /// watchOS doesn't have a browser, so this won't work there.
@@ -25,15 +36,78 @@ pub(super) fn open_browser_internal(
     _browser: Browser,
     target: &TargetType,
     options: &BrowserOptions,
-) -> Result<()> {
+) -> Result<OpenOutcome> {
     // ensure we're opening only http/https urls, failing otherwise
     let url = target.get_http_url()?;
 
     // always return true for a dry run
+    if options.dry_run {
+        return Ok(OpenOutcome::new(HandlerKind::OsDefault, None));
+    }
+
+    // universal links can end up opening a non-browser app
+    open_ns_url(url, false).map(|_| OpenOutcome::new(HandlerKind::OsDefault, None))
+}
+
+/// The default browser can't be identified on this platform
+pub(super) fn default_browser_family() -> BrowserFamily {
+    BrowserFamily::Unknown
+}
+
+/// The default browser can't be identified on this platform
+pub(super) fn default_browser_name() -> Result<String> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "default browser can't be identified on this platform",
+    ))
+}
+
+/// Browsers aren't launched via executables on this platform
+pub(super) fn browser_path(_browser: Browser) -> Result<std::path::PathBuf> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "browser executables can't be resolved on this platform",
+    ))
+}
+
+/// Urls can't be opened via commands on this platform
+pub(super) fn open_with_command_internal(
+    _command: &str,
+    _target: &TargetType,
+    _options: &BrowserOptions,
+) -> Result<OpenOutcome> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "urls can't be opened via commands on this platform",
+    ))
+}
+
+/// Urls aren't opened by running commands on this platform
+pub(super) fn resolve_internal(
+    _browser: Browser,
+    _target: &TargetType,
+    _options: &BrowserOptions,
+) -> Result<ResolvedTarget> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "commands can't be resolved on this platform",
+    ))
+}
+
+/// Open a deep link (e.g. `myapp://path`) in the app registered for it. Unlike
+/// [open_browser_internal], this isn't restricted to http(s) urls, and returns an
+/// error of kind [ErrorKind::NotFound] if `canOpenURL:` says the url can't be opened.
+pub(super) fn open_deep_link(target: &TargetType, options: &BrowserOptions) -> Result<()> {
     if options.dry_run {
         return Ok(());
     }
 
+    open_ns_url(target.0.as_str(), true)
+}
+
+/// Open `url` via UIApplication. If `ensure_openable` is true, we first check with
+/// `canOpenURL:` that some app can handle it.
+fn open_ns_url(url: &str, ensure_openable: bool) -> Result<()> {
     let app = app().ok_or(Error::new(
         ErrorKind::Other,
         "UIApplication is null, can't open url",
@@ -46,9 +120,28 @@ pub(super) fn open_browser_internal(
         ErrorKind::Other,
         "Failed creating NSURL; is the URL valid?",
     ))?;
+
+    if ensure_openable && !can_open_url(&app, &url_object) {
+        return Err(Error::new(ErrorKind::NotFound, "no app found to open url"));
+    }
+
     // empty options dictionary
     let options = NSDictionary::new();
 
+    // if the result is awaited, have it reported via the completion handler
+    #[cfg(feature = "async")]
+    if let Some(completion) = crate::future::take_completion() {
+        let handler = RcBlock::new(move |success: Bool| {
+            completion.complete(if success.as_bool() {
+                Ok(())
+            } else {
+                Err(Error::new(ErrorKind::Other, "url could not be opened"))
+            })
+        });
+        open_url(&app, &url_object, &options, Some(&handler));
+        return Ok(());
+    }
+
     // Open url
     open_url(&app, &url_object, &options, None);
     Ok(())
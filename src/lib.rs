@@ -18,11 +18,11 @@
 //! |-----------------------|-----------|----------|-------------|
 //! | macOS                 | ✅        | default + [others](https://docs.rs/webbrowser/latest/webbrowser/enum.Browser.html) | ✅ |
 //! | windows               | ✅        | default only | ✅ |
-//! | linux/wsl             | ✅        | default only (respects $BROWSER env var, so can be used with other browsers) | ✅ |
+//! | linux/wsl             | ✅        | default + [others](https://docs.rs/webbrowser/latest/webbrowser/enum.Browser.html) (respects $BROWSER env var) | ✅ |
 //! | android               | ✅        | default only | ✅ |
 //! | iOS/tvOS/visionOS     | ✅        | default only | ✅ |
 //! | wasm                  | ✅        | default only | ✅ |
-//! | unix (*bsd, aix etc.) | ✅        | default only (respects $BROWSER env var, so can be used with other browsers) | Manual |
+//! | unix (*bsd, aix etc.) | ✅        | default + [others](https://docs.rs/webbrowser/latest/webbrowser/enum.Browser.html) (respects $BROWSER env var) | Manual |
 //!
 //! ## Consistent Behaviour
 //! `webbrowser` defines consistent behaviour on all platforms as follows:
@@ -38,6 +38,11 @@
 //! * `hardened` - this disables handling of non-http(s) urls (e.g. `file:///`) as a hard security precaution
 //! * `disable-wsl` - this disables WSL `file` implementation (`http` still works)
 //! * `wasm-console` - this enables logging to wasm console (valid only on wasm platform)
+//! * `callback` - this enables `open_and_wait_for_callback()`, to wait for a local callback after opening a url (e.g. in OAuth flows)
+//! * `async` - this enables `open_async()` & `open_browser_with_options_async()`, returning a future which resolves once the OS confirms the launch (asynchronously reported on iOS/tvOS/visionOS only)
+//! * `tokio` - this enables the `async` feature, and opens urls on the tokio blocking thread pool, when `open_async()` is called from within a tokio runtime
+//! * `gio` - this uses the native GIO API on GNOME like desktops, instead of spawning `gio open` (requires glib to be installed). `gio open` is still spawned when a display or user is specified via `BrowserOptions`, as the native API always launches within the current session
+//! * `dbus` - this uses D-Bus on linux/*bsd, in `reveal_in_file_manager()` to select the item in the file manager, to activate Firefox or Chrome when opening a url in them specifically, before falling back to running their binaries, and to open urls via the desktop portal, if `BrowserOptions::with_use_portal()` is set (requires libdbus to be installed)
 
 #[cfg_attr(
     any(target_os = "ios", target_os = "tvos", target_os = "visionos"),
@@ -80,8 +85,21 @@ mod os;
 ))]
 pub(crate) mod common;
 
+#[cfg(not(target_family = "wasm"))]
+mod shortcut;
+
+#[cfg(feature = "callback")]
+mod callback;
+#[cfg(feature = "callback")]
+pub use callback::{open_and_wait_for_callback, HttpRequestInfo};
+
+#[cfg(feature = "async")]
+mod future;
+#[cfg(feature = "async")]
+pub use future::{open_async, open_browser_async, open_browser_with_options_async, OpenFuture};
+
 use std::fmt::Display;
-use std::io::{Error, ErrorKind, Result};
+use std::io::{self, ErrorKind, Result};
 use std::ops::Deref;
 use std::str::FromStr;
 use std::{error, fmt};
@@ -110,9 +128,93 @@ pub enum Browser {
 
     ///Haiku's WebPositive
     WebPositive,
+
+    ///Brave
+    Brave,
+
+    ///Microsoft Edge
+    Edge,
+
+    ///Tor Browser
+    TorBrowser,
+
+    ///GNOME Web (Epiphany)
+    Epiphany,
+
+    ///KDE's Konqueror
+    Konqueror,
 }
 
 impl Browser {
+    /// Returns all the browser variants, e.g. to check which ones are available via
+    /// [Browser::exists]
+    pub fn all() -> &'static [Browser] {
+        &[
+            Browser::Default,
+            Browser::Firefox,
+            Browser::InternetExplorer,
+            Browser::Chrome,
+            Browser::Opera,
+            Browser::Safari,
+            Browser::WebPositive,
+            Browser::Brave,
+            Browser::Edge,
+            Browser::TorBrowser,
+            Browser::Epiphany,
+            Browser::Konqueror,
+        ]
+    }
+
+    /// Returns the family of this browser, which, for [Browser::Default], is that of the default
+    /// browser as per [classify_default]
+    fn family(self) -> BrowserFamily {
+        match self {
+            Browser::Default => classify_default(),
+            Browser::Chrome | Browser::Opera | Browser::Brave | Browser::Edge => {
+                BrowserFamily::Chromium
+            }
+            Browser::Firefox | Browser::TorBrowser => BrowserFamily::Firefox,
+            Browser::Safari | Browser::WebPositive | Browser::Epiphany => BrowserFamily::WebKit,
+            Browser::InternetExplorer | Browser::Konqueror => BrowserFamily::Unknown,
+        }
+    }
+
+    /// Returns the specific browsers detected in the system, i.e. all of [Browser::all] except
+    /// [Browser::Default], for which [Browser::exists] is true, in the same order
+    pub fn installed() -> Vec<Browser> {
+        Browser::all()
+            .iter()
+            .copied()
+            .filter(|browser| *browser != Browser::Default && browser.exists())
+            .collect()
+    }
+
+    /// Returns the path of the executable which would be run to open a url in this browser,
+    /// without running it, e.g. for diagnostics. Platform specific behaviour is as follows:
+    /// * **Linux/\*bsd** - the first command which the fallback chain would run, searched for in
+    ///   `PATH` as usual. For [Browser::Default], this may well be a helper like `xdg-open`, rather
+    ///   than the browser itself.
+    /// * **macOS** - the path of the `.app` bundle, or of the browser binary, if the app bundle
+    ///   isn't installed (e.g. when installed via Homebrew)
+    /// * **Windows** - the executable in the command registered for `https` urls (or for `http`
+    ///   urls, if none is registered for `https`), or for Brave/Edge, in the command registered
+    ///   for their ProgIds
+    /// * **All other platforms** - an error of kind [ErrorKind::Unsupported] is returned
+    ///
+    /// An error of kind [ErrorKind::NotFound] is returned if the browser isn't installed.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use webbrowser::Browser;
+    ///
+    /// if let Ok(path) = Browser::Firefox.path() {
+    ///     println!("firefox is at {}", path.display());
+    /// }
+    /// ```
+    pub fn path(&self) -> Result<std::path::PathBuf> {
+        os::browser_path(*self)
+    }
+
     /// Returns true if there is likely a browser detected in the system
     pub fn is_available() -> bool {
         Browser::Default.exists()
@@ -145,6 +247,88 @@ impl error::Error for ParseBrowserError {
     }
 }
 
+/// The error returned by [open_browser_detailed], which distinguishes the causes of failure that
+/// are otherwise only reflected in the [ErrorKind] of the [std::io::Error] returned by the other
+/// functions.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// No browser, or other means of opening the url, was found
+    BrowserNotFound(String),
+
+    /// The url, or one of the options specified along with it, is invalid
+    InvalidUrl(String),
+
+    /// A browser was found, but launching it failed
+    LaunchFailed(io::Error),
+
+    /// The operation isn't supported on this platform, or for this browser
+    Unsupported(String),
+
+    /// The browser blocked the popup window, as happens on wasm without a user gesture
+    PopupBlocked,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::BrowserNotFound(msg) | Error::InvalidUrl(msg) | Error::Unsupported(msg) => {
+                f.write_str(msg)
+            }
+            Error::LaunchFailed(err) => err.fmt(f),
+            Error::PopupBlocked => {
+                f.write_str("popup blocked? window detected, but open_url failed")
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::LaunchFailed(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(mut err: io::Error) -> Self {
+        // an error of ours may have been wrapped, e.g. for a blocked popup, in which case we take
+        // it out, leaving a placeholder in the io::Error we're about to drop
+        if let Some(inner) = err
+            .get_mut()
+            .and_then(|inner| inner.downcast_mut::<Error>())
+        {
+            return std::mem::replace(inner, Error::PopupBlocked);
+        }
+        match err.kind() {
+            ErrorKind::NotFound => Error::BrowserNotFound(err.to_string()),
+            ErrorKind::InvalidInput => Error::InvalidUrl(err.to_string()),
+            ErrorKind::Unsupported => Error::Unsupported(err.to_string()),
+            _ => Error::LaunchFailed(err),
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::LaunchFailed(err) => err,
+            err => {
+                let kind = match err {
+                    Error::BrowserNotFound(_) => ErrorKind::NotFound,
+                    Error::InvalidUrl(_) => ErrorKind::InvalidInput,
+                    Error::Unsupported(_) => ErrorKind::Unsupported,
+                    _ => ErrorKind::Other,
+                };
+                // the error is retained, so that it can be recovered by From<io::Error>
+                io::Error::new(kind, err)
+            }
+        }
+    }
+}
+
 impl fmt::Display for Browser {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -155,6 +339,11 @@ impl fmt::Display for Browser {
             Browser::Opera => f.write_str("Opera"),
             Browser::Safari => f.write_str("Safari"),
             Browser::WebPositive => f.write_str("WebPositive"),
+            Browser::Brave => f.write_str("Brave"),
+            Browser::Edge => f.write_str("Microsoft Edge"),
+            Browser::TorBrowser => f.write_str("Tor Browser"),
+            Browser::Epiphany => f.write_str("GNOME Web"),
+            Browser::Konqueror => f.write_str("Konqueror"),
         }
     }
 }
@@ -171,6 +360,11 @@ impl FromStr for Browser {
             "opera" => Ok(Browser::Opera),
             "safari" => Ok(Browser::Safari),
             "webpositive" => Ok(Browser::WebPositive),
+            "brave" => Ok(Browser::Brave),
+            "edge" | "msedge" | "microsoft edge" => Ok(Browser::Edge),
+            "tor" | "torbrowser" | "tor browser" => Ok(Browser::TorBrowser),
+            "epiphany" | "gnome-web" | "gnome web" => Ok(Browser::Epiphany),
+            "konqueror" => Ok(Browser::Konqueror),
             _ => Err(ParseBrowserError),
         }
     }
@@ -181,10 +375,65 @@ impl FromStr for Browser {
 /// not guaranteed to be honoured. Use [BrowserOptions::new()] to create.
 ///
 /// e.g. by default, we suppress stdout/stderr, but that behaviour can be overridden here
+///
+/// # Launch options
+///
+/// Options which work by passing flags to the browser, or by otherwise changing how its process
+/// is run, are honoured only when we launch the browser command ourselves, i.e. on linux/*bsd and
+/// windows, and not when the url gets handed to helpers like `xdg-open`. Such options are marked
+/// as launch options in their docs.
 pub struct BrowserOptions {
     suppress_output: bool,
     target_hint: String,
     dry_run: bool,
+    display: Option<String>,
+    deescalate: bool,
+    raw_url: bool,
+    firefox_container: Option<String>,
+    new_window: bool,
+    clean_env: bool,
+    wrapper_command: Option<String>,
+    startup_notify: bool,
+    timezone: Option<String>,
+    https_upgrade: bool,
+    unknown_scheme_policy: Option<UnknownSchemePolicy>,
+    allow_non_browser_schemes: bool,
+    systemd_scope: Option<Vec<(String, String)>>,
+    binary_overrides: Vec<(Browser, Vec<String>)>,
+    scheme_browsers: Vec<(String, Browser)>,
+    flatpak_app: Option<String>,
+    wsl_windows_config: Option<(
+        std::path::PathBuf,
+        std::path::PathBuf,
+        Option<std::path::PathBuf>,
+    )>,
+    wsl_routing: bool,
+    target_user: Option<u32>,
+    force_accessibility: bool,
+    cache_dir: Option<std::path::PathBuf>,
+    no_path_search: bool,
+    wait_for_exit: bool,
+    ignore_cert_errors: bool,
+    allow_headless: bool,
+    suppress_first_run: bool,
+    virtual_desktop: Option<usize>,
+    remote_debugging_port: Option<u16>,
+    verify_launch: bool,
+    prefer_text: bool,
+    incognito: bool,
+    use_portal: bool,
+    verify_file_exists: bool,
+    accept_languages: Vec<String>,
+    fullscreen: bool,
+    maximized: bool,
+    window_bounds: Option<(i32, i32, u32, u32)>,
+    timing_sink: Option<SharedTimingSink>,
+    macos_bundle_id: Option<String>,
+    profile: Option<String>,
+    disable_gpu: bool,
+    kiosk: bool,
+    args: Vec<String>,
+    display_platform: Option<DisplayPlatform>,
 }
 
 impl fmt::Display for BrowserOptions {
@@ -203,6 +452,50 @@ impl std::default::Default for BrowserOptions {
             suppress_output: true,
             target_hint,
             dry_run: false,
+            display: None,
+            deescalate: false,
+            raw_url: false,
+            firefox_container: None,
+            new_window: false,
+            clean_env: false,
+            wrapper_command: None,
+            startup_notify: true,
+            timezone: None,
+            https_upgrade: false,
+            unknown_scheme_policy: None,
+            allow_non_browser_schemes: false,
+            systemd_scope: None,
+            binary_overrides: Vec::new(),
+            scheme_browsers: Vec::new(),
+            flatpak_app: None,
+            wsl_windows_config: None,
+            wsl_routing: false,
+            target_user: None,
+            force_accessibility: false,
+            cache_dir: None,
+            no_path_search: false,
+            wait_for_exit: false,
+            ignore_cert_errors: false,
+            allow_headless: false,
+            suppress_first_run: false,
+            virtual_desktop: None,
+            remote_debugging_port: None,
+            verify_launch: false,
+            prefer_text: false,
+            incognito: false,
+            use_portal: false,
+            verify_file_exists: false,
+            accept_languages: Vec::new(),
+            fullscreen: false,
+            maximized: false,
+            window_bounds: None,
+            timing_sink: None,
+            macos_bundle_id: None,
+            profile: None,
+            disable_gpu: false,
+            kiosk: false,
+            args: Vec::new(),
+            display_platform: None,
         }
     }
 }
@@ -238,6 +531,960 @@ impl BrowserOptions {
         self.dry_run = dry_run;
         self
     }
+
+    /// Open the browser on the specified display, which is useful on multi-seat systems. This
+    /// can either be an X11 display (e.g. `:1` or `host:0.1`), which gets set as `DISPLAY` for
+    /// the browser, or a Wayland display (e.g. `wayland-1`), which gets set as `WAYLAND_DISPLAY`.
+    ///
+    /// This is honoured only on linux/*bsd. An error of kind [ErrorKind::InvalidInput] is
+    /// returned while opening, if this doesn't look like a valid display.
+    pub fn with_display(&mut self, display: &str) -> &mut Self {
+        self.display = Some(display.to_owned());
+        self
+    }
+
+    /// When running as root under `sudo`, the browser gets resolved and launched in root's
+    /// context, which typically fails as root can't access the invoking user's display. Setting
+    /// this resolves the browser using the home directory of the invoking user (i.e. `$SUDO_USER`),
+    /// and launches it via `sudo -u $SUDO_USER`, preserving `DISPLAY`, `WAYLAND_DISPLAY` and
+    /// `XAUTHORITY`.
+    ///
+    /// This is honoured only on linux/*bsd, and requires sudo 1.8.21 or above.
+    pub fn with_deescalate(&mut self, deescalate: bool) -> &mut Self {
+        self.deescalate = deescalate;
+        self
+    }
+
+    /// Pass the url to the browser exactly as specified, instead of the normalized form produced
+    /// by url parsing (e.g. lowercased host, resolved dot segments, re-encoded path). This is
+    /// useful for servers which are sensitive to the exact encoding of the path.
+    ///
+    /// The url is still parsed for validation, and for checks like the ones done by the `hardened`
+    /// feature, but the string passed to the browser is the original one. The only changes made
+    /// are percent-encoding of whitespace, control characters, and `"<>\``, which are never valid
+    /// in a url, but can break the browser's command line, and the punycode encoding of an
+    /// internationalized host. This has no effect on local file paths.
+    pub fn with_raw_url(&mut self, raw_url: bool) -> &mut Self {
+        self.raw_url = raw_url;
+        self
+    }
+
+    /// Open the url in the specified Firefox container, using the `ext+container:` scheme
+    /// understood by the [Open external links in a container](https://addons.mozilla.org/firefox/addon/open-url-in-container/)
+    /// addon, which needs to be installed alongside Multi-Account Containers.
+    ///
+    /// This is honoured only when explicitly opening [Browser::Firefox], and ignored for all other
+    /// browsers, including [Browser::Default] (as we can't reliably tell if that's Firefox).
+    pub fn with_firefox_container(&mut self, container: &str) -> &mut Self {
+        self.firefox_container = Some(container.to_owned());
+        self
+    }
+
+    /// Launch the browser with a clean environment, instead of inheriting ours, so that env vars
+    /// like `LD_PRELOAD` or `MOZ_*` don't affect the browser. Only the following env vars are
+    /// retained (if set):
+    /// * **Linux/\*BSD** - `PATH`, `HOME`, `USER`, `LOGNAME`, `LANG`, `DISPLAY`, `WAYLAND_DISPLAY`,
+    ///   `XAUTHORITY`, `XDG_RUNTIME_DIR` and `DBUS_SESSION_BUS_ADDRESS`
+    /// * **Windows** - `PATH`, `SYSTEMROOT`, `WINDIR`, `COMSPEC`, `USERPROFILE`, `APPDATA`,
+    ///   `LOCALAPPDATA`, `TEMP`, `TMP` and `PROGRAMFILES`
+    ///
+    /// This is ignored on platforms where we don't spawn the browser command ourselves, i.e.
+    /// macOS, Android, iOS and wasm, and when the `gio` feature is used to open the url.
+    pub fn with_clean_env(&mut self, clean_env: bool) -> &mut Self {
+        self.clean_env = clean_env;
+        self
+    }
+
+    /// Launch the browser via the specified wrapper command, e.g. an SSO helper. The browser
+    /// command is still resolved as usual, and is then passed as arguments to the wrapper, i.e.
+    /// with a wrapper of `sso-open --realm corp`, a resolved browser command of `firefox <url>`
+    /// gets launched as `sso-open --realm corp firefox <url>`. Arguments with spaces in `wrapper`
+    /// can be specified using double quotes.
+    ///
+    /// This is ignored on platforms where we don't spawn the browser command ourselves, i.e.
+    /// macOS, Android, iOS and wasm, and when the `gio` feature is used to open the url.
+    pub fn with_wrapper_command(&mut self, wrapper: &str) -> &mut Self {
+        self.wrapper_command = Some(wrapper.to_owned());
+        self
+    }
+
+    /// Enable/disable [startup notification](https://specifications.freedesktop.org/startup-notification-spec/latest/),
+    /// which is enabled by default. When enabled, the browser is launched with `DESKTOP_STARTUP_ID`
+    /// set to an id of the form `webbrowser-<pid>-<hostname>-<program>-<sequence>` (and with
+    /// `--startup-id <id>` for `exo-open`), allowing the desktop environment to show launch
+    /// feedback, and to manage the browser's focus.
+    ///
+    /// This is honoured only on linux/*bsd, and not for text browsers.
+    pub fn with_startup_notify(&mut self, startup_notify: bool) -> &mut Self {
+        self.startup_notify = startup_notify;
+        self
+    }
+
+    /// Launch the browser as the user with the specified `uid`, in that user's graphical session.
+    /// This is meant for system services (which don't have a session of their own) that need to
+    /// open a browser for the user logged in at the console. The browser gets resolved and
+    /// launched like with [BrowserOptions::with_deescalate], but for this user, with `DISPLAY`,
+    /// `WAYLAND_DISPLAY`, `XAUTHORITY`, `XDG_RUNTIME_DIR` and `DBUS_SESSION_BUS_ADDRESS` set up as
+    /// per the user's session, which is looked up via `loginctl`.
+    ///
+    /// This requires the calling process to run as root (or otherwise be allowed to run commands
+    /// as that user via `sudo -u` without a password), and the user to have an active graphical
+    /// session managed by systemd-logind. An error of kind [ErrorKind::NotFound] is returned while
+    /// opening, if `uid` isn't a known user.
+    ///
+    /// This is honoured only on linux/*bsd, and requires sudo 1.8.21 or above.
+    pub fn with_target_user(&mut self, uid: u32) -> &mut Self {
+        self.target_user = Some(uid);
+        self
+    }
+
+    /// Hint to the browser to enable its accessibility support from the start, e.g. for testing
+    /// keyboard navigation or screen reader behaviour. For Chromium based browsers (Chrome,
+    /// Chromium, Edge, Brave, Vivaldi, Opera), this passes `--force-renderer-accessibility`.
+    /// Firefox enables accessibility on demand, so nothing is passed to it, and this is ignored
+    /// for all other browsers. Note that this is just a hint, as the url may get opened in an
+    /// already running browser instance.
+    ///
+    /// This is a [launch option](BrowserOptions#launch-options).
+    pub fn with_force_accessibility(&mut self, force_accessibility: bool) -> &mut Self {
+        self.force_accessibility = force_accessibility;
+        self
+    }
+
+    /// Go through the usual means of opening the default browser, even if running inside a
+    /// container like Docker or Podman, without a display or `$BROWSER` configured.
+    ///
+    /// By default, opening a url in such a case fails quickly with an error of kind
+    /// [ErrorKind::Unsupported], as there's almost never a browser available there, and trying
+    /// all the fallbacks just wastes time. Containers are detected via the presence of
+    /// `/.dockerenv` or `/run/.containerenv`, the `container` env var, or hints in
+    /// `/proc/1/cgroup`. Flatpak isn't treated as such a container. Dry runs aren't affected, as
+    /// they only resolve the browser.
+    ///
+    /// This is relevant only on linux/*bsd, and only for [Browser::Default].
+    pub fn with_allow_headless(&mut self, allow_headless: bool) -> &mut Self {
+        self.allow_headless = allow_headless;
+        self
+    }
+
+    /// Move the browser window to the virtual desktop at the specified (zero based) index, after
+    /// launching it, e.g. for dashboards spread across virtual desktops.
+    ///
+    /// This is best-effort, as window placement is inherently racy. We wait for up to 5 seconds
+    /// for a window of the launched browser process to show up, and then move it via the
+    /// documented `IVirtualDesktopManager` COM interface, with the ids of the virtual desktops
+    /// read from the registry, as there's no documented API to list them. Failures are logged,
+    /// but don't fail the opening of the url. Notably, no window shows up when the url is handed
+    /// over to an already running browser instance, and some Windows versions deny moving windows
+    /// of other processes.
+    ///
+    /// This is honoured only on Windows, and ignored on all other platforms.
+    pub fn with_virtual_desktop(&mut self, desktop: usize) -> &mut Self {
+        self.virtual_desktop = Some(desktop);
+        self
+    }
+
+    /// Prefer a text browser, e.g. `lynx` or `w3m`, over GUI browsers, when opening a url in the
+    /// default browser. This is useful in SSH or headless contexts, where a text browser is the
+    /// right choice. The first of the known text browsers found in `PATH` is run in the foreground,
+    /// blocking till it exits. If none are found, we fall back to the usual means of opening the
+    /// url, which may well open a GUI browser.
+    ///
+    /// This is honoured only on linux/*bsd, and ignored on all other platforms.
+    pub fn with_prefer_text(&mut self, prefer_text: bool) -> &mut Self {
+        self.prefer_text = prefer_text;
+        self
+    }
+
+    /// Check that the local file pointed to by a `file://` url exists, before opening it, failing
+    /// with an error of kind [ErrorKind::NotFound] otherwise. This helps catch bugs in generated
+    /// paths early, instead of the browser showing a "file not found" page. By default, such urls
+    /// are handed to the browser as is.
+    ///
+    /// This applies to local file paths too, as they're converted to `file://` urls, and is
+    /// ignored on wasm.
+    pub fn with_verify_file_exists(&mut self, verify_file_exists: bool) -> &mut Self {
+        self.verify_file_exists = verify_file_exists;
+        self
+    }
+
+    /// Open the url in the default browser via the `OpenURI` method of the
+    /// [XDG desktop portal](https://flatpak.github.io/xdg-desktop-portal/docs/doc-org.freedesktop.portal.OpenURI.html),
+    /// even when not running inside Flatpak. This is tried before all other means of opening the
+    /// url, and if the portal isn't available, we fall back to them as usual. Local files aren't
+    /// opened via the portal, as it requires them to be passed as file descriptors.
+    ///
+    /// This is honoured only on linux/*bsd, and requires the `dbus` feature to be enabled. It's
+    /// ignored otherwise.
+    pub fn with_use_portal(&mut self, use_portal: bool) -> &mut Self {
+        self.use_portal = use_portal;
+        self
+    }
+
+    /// Open the url in a new window, instead of the browser's own default, which usually is a new
+    /// tab in an existing window.
+    ///
+    /// This is a hint, which is honoured only when the browser is known to be Firefox or Chromium
+    /// based, in which case `-new-window` or `--new-window` is passed to it, respectively. In
+    /// particular, it's ignored when the url is opened via helpers like `xdg-open`, and on windows
+    /// when the default browser's command from the registry isn't recognised. On macOS, a new
+    /// instance of the browser is requested from Launch Services instead, similar to `open -n`.
+    pub fn with_new_window(&mut self, new_window: bool) -> &mut Self {
+        self.new_window = new_window;
+        self
+    }
+
+    /// Open the url in a private window, e.g. for OAuth flows which need a clean session, without
+    /// the cookies of the user's regular profile.
+    ///
+    /// For Chromium based browsers, this passes `--incognito`, and for Firefox `-private-window`.
+    /// This is a hint, which is silently ignored if the browser can't be determined, or has no
+    /// such flag, e.g. when the url is opened via helpers like `xdg-open`, or in Safari.
+    ///
+    /// This is honoured on linux/*bsd and windows when we launch the browser command ourselves,
+    /// and on macOS, where the browser's binary is then run directly, instead of via Launch
+    /// Services.
+    pub fn with_incognito(&mut self, incognito: bool) -> &mut Self {
+        self.incognito = incognito;
+        self
+    }
+
+    /// Suppress the first run experience of the browser, e.g. the welcome wizard & the default
+    /// browser check, which get in the way of automation when a new or clean profile is used.
+    ///
+    /// For Chromium based browsers, this passes `--no-first-run --no-default-browser-check`.
+    /// Firefox has no equivalent flags, so it's run with a temporary profile instead, with prefs
+    /// which turn off the welcome & what's new pages, and the default browser check. This is
+    /// ignored for all other browsers.
+    ///
+    /// This is a [launch option](BrowserOptions#launch-options).
+    pub fn with_suppress_first_run(&mut self, suppress_first_run: bool) -> &mut Self {
+        self.suppress_first_run = suppress_first_run;
+        self
+    }
+
+    /// Ignore TLS certificate errors, e.g. for testing internal https sites with self-signed
+    /// certificates.
+    ///
+    /// **This is a security risk**, as it makes the browser trust any certificate, allowing the
+    /// traffic to be intercepted, so this must only ever be used in testing, and never with urls
+    /// provided by untrusted sources.
+    ///
+    /// For Chromium based browsers, this passes `--ignore-certificate-errors`. Firefox has no
+    /// equivalent flag, so it's run with a temporary profile instead, with prefs which allow
+    /// adding a permanent exception for such a certificate from the error page, even for hosts
+    /// which use HSTS. This is ignored for all other browsers. Note that an already running
+    /// Chromium instance may get the url, without the flag taking effect.
+    ///
+    /// This is a [launch option](BrowserOptions#launch-options).
+    pub fn with_ignore_cert_errors(&mut self, ignore_cert_errors: bool) -> &mut Self {
+        self.ignore_cert_errors = ignore_cert_errors;
+        self
+    }
+
+    /// Open the url in the specified browser profile. For Chromium based browsers, this passes
+    /// `--profile-directory=<profile>`, where `profile` is the name of the profile's directory,
+    /// e.g. `Profile 1`, and for Firefox `-P <profile>`, where `profile` is the profile's name. This
+    /// is ignored for all other browsers.
+    ///
+    /// Some of the other options (e.g. [BrowserOptions::with_ignore_cert_errors]) run Firefox with
+    /// a temporary profile instead, which can't be combined with this, so opening the url in
+    /// Firefox then fails with an error of kind [ErrorKind::InvalidInput]. The temporary profile is
+    /// a freshly created directory, accessible only to the current user, which is removed once the
+    /// browser exits, if we wait for it (e.g. via [BrowserOptions::with_wait_for_exit]), and is
+    /// otherwise left in the temp dir for the browser to use.
+    ///
+    /// This is honoured only when a specific browser is requested, i.e. not for
+    /// [Browser::Default], as the command of the default browser isn't something we can safely
+    /// add a profile to, and only on linux/*bsd and windows.
+    pub fn with_profile(&mut self, profile: &str) -> &mut Self {
+        self.profile = Some(profile.to_owned());
+        self
+    }
+
+    /// Disable GPU acceleration, e.g. in VMs or CI environments without a usable GPU, where the
+    /// browser may otherwise crash on launch. For Chromium based browsers, this passes
+    /// `--disable-gpu --disable-software-rasterizer`, while this is a no-op for all other browsers.
+    ///
+    /// This is a [launch option](BrowserOptions#launch-options).
+    pub fn with_disable_gpu(&mut self, disable_gpu: bool) -> &mut Self {
+        self.disable_gpu = disable_gpu;
+        self
+    }
+
+    /// Run the browser natively on the specified display server, e.g. to force it onto X11 (via
+    /// XWayland) on a Wayland desktop. For Chromium based browsers, this passes
+    /// `--ozone-platform=wayland|x11`, while for Firefox, `MOZ_ENABLE_WAYLAND` is set to `1` or `0`
+    /// respectively, along with `GDK_BACKEND=x11` for the latter. This is ignored for all other
+    /// browsers.
+    ///
+    /// This is honoured only on linux/*bsd, when we launch the browser command ourselves, and not
+    /// via helpers like `xdg-open`.
+    pub fn with_display_platform(&mut self, platform: DisplayPlatform) -> &mut Self {
+        self.display_platform = Some(platform);
+        self
+    }
+
+    /// Pass the specified extra arguments to the browser, before the url, e.g. `--app=<url>`. Each
+    /// of `args` is passed as a separate argument, as is, without any shell interpretation.
+    ///
+    /// This is honoured only when a specific browser is requested, i.e. not for
+    /// [Browser::Default], as the command of the default browser may well be a helper like
+    /// `xdg-open`, which wouldn't understand them. On macOS, the browser's binary is then run
+    /// directly, instead of via Launch Services, as the latter can't pass arguments to the browser.
+    pub fn with_args(&mut self, args: &[&str]) -> &mut Self {
+        self.args = args.iter().map(|arg| arg.to_string()).collect();
+        self
+    }
+
+    /// Start the browser in kiosk mode, i.e. fullscreen without any of the browser's UI, e.g. for
+    /// digital signage. For Chromium based browsers & Firefox, this passes `--kiosk`, while this is
+    /// ignored for all other browsers. This takes precedence over [BrowserOptions::with_fullscreen]
+    /// & [BrowserOptions::with_maximized].
+    ///
+    /// This is honoured only when a specific browser is requested, i.e. not for
+    /// [Browser::Default]. On macOS, the browser's binary is then run directly, instead of via
+    /// Launch Services, as the latter can't pass arguments to the browser.
+    pub fn with_kiosk(&mut self, kiosk: bool) -> &mut Self {
+        self.kiosk = kiosk;
+        self
+    }
+
+    /// Start the browser in fullscreen, e.g. for presentations or signage. For Chromium based
+    /// browsers, this passes `--start-fullscreen`, and for Firefox `--kiosk`, which also hides
+    /// the browser's UI. This is ignored for all other browsers, and takes precedence over
+    /// [BrowserOptions::with_maximized], if both are set.
+    ///
+    /// This is a [launch option](BrowserOptions#launch-options). Also note that an already running
+    /// instance of the browser may get the url, without the flag taking effect.
+    pub fn with_fullscreen(&mut self, fullscreen: bool) -> &mut Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    /// Start the browser with a maximized window. For Chromium based browsers, this passes
+    /// `--start-maximized`, while this is ignored for all other browsers, as Firefox has no such
+    /// flag. If [BrowserOptions::with_fullscreen] is set too, that takes precedence.
+    ///
+    /// This is a [launch option](BrowserOptions#launch-options).
+    pub fn with_maximized(&mut self, maximized: bool) -> &mut Self {
+        self.maximized = maximized;
+        self
+    }
+
+    /// Open the browser window at position `x`, `y` (of its top left corner, in screen
+    /// coordinates), with the specified `width` & `height`. This is best effort, and is ignored
+    /// if [BrowserOptions::with_kiosk], [BrowserOptions::with_fullscreen] or
+    /// [BrowserOptions::with_maximized] is set. Platform specific behaviour is as follows:
+    /// * **linux/\*bsd & windows** - Chromium based browsers are passed `--window-position` and
+    ///   `--window-size`, while Firefox is passed `-width` and `-height`, as it can't be positioned.
+    ///   This applies only when we launch the browser command ourselves, and not via helpers like
+    ///   `xdg-open`.
+    /// * **macOS** - Launch Services can't size windows, so once Safari or Google Chrome has been
+    ///   launched, `osascript` is run in the background to set the bounds of its front window.
+    ///   Other browsers aren't scriptable this way, so are left as is. This is inherently racy, as
+    ///   the script may get to a different window than the one opening the url, e.g. if the
+    ///   browser was already running.
+    pub fn with_window_position(&mut self, x: i32, y: i32, width: u32, height: u32) -> &mut Self {
+        self.window_bounds = Some((x, y, width, height));
+        self
+    }
+
+    /// Use the specified languages, in order of preference, for the `Accept-Language` header sent
+    /// by the browser, e.g. `&["en-US", "en", "fr"]`, for testing language negotiation.
+    ///
+    /// For Chromium based browsers, this passes `--accept-lang=en-US,en,fr`. Firefox has no
+    /// equivalent flag, so it's run with a temporary profile instead, with the
+    /// `intl.accept_languages` pref set. This is ignored for all other browsers. An error is
+    /// returned while opening, if any of the languages isn't a valid language tag.
+    ///
+    /// This is a [launch option](BrowserOptions#launch-options).
+    pub fn with_accept_languages(&mut self, languages: &[&str]) -> &mut Self {
+        self.accept_languages = languages.iter().map(|lang| lang.to_string()).collect();
+        self
+    }
+
+    /// Use the specified directory as the browser's disk cache, e.g. for benchmarking with a cold
+    /// or a known cache. For Chromium based browsers, this passes `--disk-cache-dir=<cache_dir>`,
+    /// while this is ignored for all other browsers. An error of kind [ErrorKind::InvalidInput] is
+    /// returned while opening, if `cache_dir`, or its closest existing ancestor, isn't a directory.
+    /// The directory gets created if it doesn't exist, right before launching a Chromium based
+    /// browser, but not for dry runs.
+    ///
+    /// This is a [launch option](BrowserOptions#launch-options).
+    pub fn with_cache_dir(&mut self, cache_dir: &std::path::Path) -> &mut Self {
+        self.cache_dir = Some(cache_dir.to_owned());
+        self
+    }
+
+    /// Don't search `PATH` for any executable, and instead only run commands specified as absolute
+    /// paths. This guards against a tampered `PATH` in privileged or otherwise sensitive contexts.
+    /// Browsers can still be run via an absolute `$BROWSER`, or absolute names passed to
+    /// [BrowserOptions::with_binary_override].
+    ///
+    /// On linux/*bsd, this means helpers like `xdg-open` are skipped, unless found via an absolute
+    /// path, and an error is returned while opening, if some other helper would need to be run by
+    /// its bare name, e.g. `sudo` for [BrowserOptions::with_deescalate]. Similarly, with WSL, only
+    /// the windows drive mounted at `/mnt/c` is used, and [BrowserOptions::with_systemd_scope] is
+    /// ignored. On windows, an error is returned if the registered browser command isn't an
+    /// absolute path.
+    ///
+    /// This has no impact on macOS, Android, iOS and wasm, as we don't look up any executables
+    /// there.
+    pub fn with_no_path_search(&mut self, no_path_search: bool) -> &mut Self {
+        self.no_path_search = no_path_search;
+        self
+    }
+
+    /// Block till the browser process exits, even for GUI browsers, e.g. for a guided setup flow
+    /// which needs to continue only after the user is done with the browser. By default, only
+    /// text browsers are waited upon. If the browser exits with a non-zero exit code, an error
+    /// is returned which reports it. [BrowserOptions::with_suppress_output] applies as usual.
+    ///
+    /// Note that browsers which are already running typically hand the url over to the existing
+    /// instance, and exit immediately, in which case this returns quickly. Similarly, helpers like
+    /// `xdg-open` may exit as soon as they've launched the browser. As the process is gone by the
+    /// time we return, [OpenOutcome::pid] is `None` in this case.
+    ///
+    /// This is honoured only when we spawn the browser command ourselves, i.e. on linux/*bsd and
+    /// windows, or on macOS when the browser is installed as a binary instead of an app bundle.
+    pub fn with_wait_for_exit(&mut self, wait_for_exit: bool) -> &mut Self {
+        self.wait_for_exit = wait_for_exit;
+        self
+    }
+
+    /// Determines whether a GUI browser is watched for a short while after being launched, so
+    /// that if it exits unsuccessfully right away (e.g. `xdg-open` failing to find a handler),
+    /// an error is returned, and the remaining fallbacks get tried. This is disabled by default,
+    /// as it delays every launch by up to 100ms. An exit with a zero exit code is always treated
+    /// as success, as that's what browsers do when handing the url over to an already running
+    /// instance.
+    ///
+    /// This is honoured only on linux/*bsd and windows.
+    pub fn with_verify_launch(&mut self, verify_launch: bool) -> &mut Self {
+        self.verify_launch = verify_launch;
+        self
+    }
+
+    /// Launch the browser with the specified time zone (e.g. `Asia/Kolkata`), by setting `TZ` for
+    /// the browser process. This is useful for testing time zone dependent pages, and is respected
+    /// by Chromium and Firefox, as long as the browser isn't already running. An error of kind
+    /// [ErrorKind::InvalidInput] is returned while opening, if `timezone` is empty.
+    ///
+    /// This is ignored on platforms where we don't spawn the browser command ourselves, i.e.
+    /// macOS, Android, iOS and wasm, and when the `gio` feature is used to open the url.
+    pub fn with_timezone(&mut self, timezone: &str) -> &mut Self {
+        self.timezone = Some(timezone.to_owned());
+        self
+    }
+
+    /// Upgrade `http://` urls to `https://` before opening them, similar to what browsers do for
+    /// [HSTS](https://developer.mozilla.org/docs/Web/HTTP/Headers/Strict-Transport-Security) hosts.
+    /// The url gets re-parsed after the scheme is rewritten, so an explicit `:80` port is dropped,
+    /// while any other explicit port is retained as is.
+    ///
+    /// Urls with a loopback host, i.e. `localhost`, `*.localhost`, `127.0.0.0/8` or `[::1]`, are
+    /// left as `http://`, so as to not break local development servers.
+    pub fn with_https_upgrade(&mut self, https_upgrade: bool) -> &mut Self {
+        self.https_upgrade = https_upgrade;
+        self
+    }
+
+    /// Launch the browser in a transient systemd scope, via `systemd-run --user --scope`, with the
+    /// specified unit `properties` applied to it. This allows capping the resources available to
+    /// the browser, e.g. `&[("MemoryMax", "1G"), ("CPUQuota", "50%")]` launches the browser as
+    /// `systemd-run --user --scope --quiet -p MemoryMax=1G -p CPUQuota=50% <browser command>`.
+    /// An empty slice launches the browser in its own scope, without any limits.
+    ///
+    /// This needs a running systemd user session, and resource limits like `MemoryMax` need
+    /// systemd 231 or later, with cgroup v2 and the corresponding controller delegated to the
+    /// user manager. If `systemd-run` isn't found, the browser is launched without a scope. An
+    /// error of kind [ErrorKind::InvalidInput] is returned while opening, if a property name
+    /// isn't alphanumeric, or a value is empty.
+    ///
+    /// This is honoured only on linux/*bsd, and not for text browsers, or when the `gio` feature
+    /// is used to open the url.
+    pub fn with_systemd_scope(&mut self, properties: &[(&str, &str)]) -> &mut Self {
+        self.systemd_scope = Some(
+            properties
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        );
+        self
+    }
+
+    /// Override the names of the executables searched for, when opening the specified `browser`
+    /// (other than [Browser::Default]). This is useful where a browser is installed with an
+    /// unusual binary name, e.g. `&["chrome-beta"]` for [Browser::Chrome]. Names are tried in the
+    /// order specified, and can be absolute paths too. The default names are:
+    /// * [Browser::Firefox] - `firefox`
+    /// * [Browser::Chrome] - `google-chrome`, `google-chrome-stable` and `chrome`
+    /// * [Browser::Opera] - `opera`
+    /// * [Browser::WebPositive] - `WebPositive`
+    /// * [Browser::Brave] - `brave-browser` and `brave`
+    /// * [Browser::Edge] - `microsoft-edge`, `microsoft-edge-stable` and `msedge`
+    /// * [Browser::TorBrowser] - `torbrowser-launcher`, and `org.torproject.torbrowser-launcher`
+    ///   as exported by its Flatpak
+    /// * [Browser::Epiphany] - `epiphany`, and `org.gnome.Epiphany` as exported by its Flatpak
+    /// * [Browser::Konqueror] - `konqueror`
+    ///
+    /// This is honoured only on linux/*bsd, where these are searched for in `PATH`, and on macOS,
+    /// where these are searched for in the Homebrew bin directories, if the app bundle isn't found.
+    pub fn with_binary_override(&mut self, browser: Browser, binaries: &[&str]) -> &mut Self {
+        let binaries = binaries.iter().map(|name| name.to_string()).collect();
+        match self
+            .binary_overrides
+            .iter_mut()
+            .find(|(b, _)| *b == browser)
+        {
+            Some((_, existing)) => *existing = binaries,
+            None => self.binary_overrides.push((browser, binaries)),
+        }
+        self
+    }
+
+    /// Open specific browsers (i.e. other than [Browser::Default]) by running the Flatpak app with
+    /// the specified `app_id` (e.g. `org.mozilla.firefox`) via `flatpak run`, instead of searching
+    /// for their binaries. Without this, the Flatpaks of known browsers are used anyway, if their
+    /// binaries aren't found, i.e. `org.mozilla.firefox`, `com.google.Chrome`, `com.opera.Opera`,
+    /// `com.brave.Browser`, `com.microsoft.Edge`, `org.torproject.torbrowser-launcher`,
+    /// `org.gnome.Epiphany` and `org.kde.konqueror`.
+    ///
+    /// Note that browser specific flags, e.g. for [BrowserOptions::with_incognito], aren't passed to
+    /// Flatpak apps, as the browser can't be identified from the command.
+    ///
+    /// This is honoured only on linux/*bsd.
+    pub fn with_flatpak_app(&mut self, app_id: &str) -> &mut Self {
+        self.flatpak_app = Some(app_id.to_owned());
+        self
+    }
+
+    /// Use the specified Windows paths when opening urls from within WSL, instead of detecting them
+    /// via `PATH`, e.g. when the Windows drive is mounted somewhere other than `/mnt/c`, or when
+    /// Windows dirs aren't added to `PATH` (`appendWindowsPath=false` in `wsl.conf`). `root` is the
+    /// mount point of the Windows drive (e.g. `/mnt/c`), `cmd_path` the path of `cmd.exe`, and
+    /// `powershell_path` that of `powershell.exe`, if available. All of them need to be absolute.
+    ///
+    /// This is honoured only on linux, when running inside WSL.
+    pub fn with_wsl_windows_config(
+        &mut self,
+        root: &std::path::Path,
+        cmd_path: &std::path::Path,
+        powershell_path: Option<&std::path::Path>,
+    ) -> &mut Self {
+        self.wsl_windows_config = Some((
+            root.to_path_buf(),
+            cmd_path.to_path_buf(),
+            powershell_path.map(std::path::Path::to_path_buf),
+        ));
+        self
+    }
+
+    /// When opening urls from within WSL, pick between the Windows and the linux browser as per
+    /// the url, instead of always using the Windows one: remote urls get opened in the Windows
+    /// default browser, while local files on the linux side (e.g. under `/home`) get opened in the
+    /// linux default browser via WSLg, if a display is available. Files on a Windows drive, i.e.
+    /// under the `root` of [BrowserOptions::with_wsl_windows_config], or under `/mnt/<drive>` by
+    /// default, are still opened in the Windows browser.
+    ///
+    /// This is honoured only on linux, when running inside WSL.
+    pub fn with_wsl_routing(&mut self, auto: bool) -> &mut Self {
+        self.wsl_routing = auto;
+        self
+    }
+
+    /// Specify a `sink` to be called with the time taken to open each url, from the call to
+    /// [open_browser_with_options] (or any of the other functions opening a url with these
+    /// options) till the platform specific launch returns, or, for a dry run, till the browser
+    /// is resolved. It's called whether or not the url gets opened successfully, but not if the
+    /// url or options are found to be invalid upfront. This helps in finding out how much time
+    /// goes into e.g. searching `PATH` or running `xdg-settings`, in conjunction with
+    /// [cache_default_browser].
+    ///
+    /// This is a measurement hook for diagnostics, and makes no guarantees, e.g. as to when the
+    /// browser window actually shows up, as browsers are typically launched in the background. On
+    /// macOS, the urls opened via [open_all] in a single launch are reported as one. This isn't
+    /// supported on wasm, where the sink is never called.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use webbrowser::{open_browser_with_options, Browser, BrowserOptions};
+    ///
+    /// let mut options = BrowserOptions::new();
+    /// options.with_timing_sink(Box::new(|elapsed| eprintln!("opened in {elapsed:?}")));
+    /// let _ = open_browser_with_options(Browser::Default, "https://example.com", &options);
+    /// ```
+    pub fn with_timing_sink(&mut self, sink: TimingSink) -> &mut Self {
+        self.timing_sink = Some(SharedTimingSink(std::sync::Arc::new(
+            std::sync::Mutex::new(sink),
+        )));
+        self
+    }
+
+    /// Open urls in the app with the specified `bundle_id` (e.g. `org.mozilla.firefox`), as
+    /// resolved via Launch Services, instead of in the app at the usual `/Applications/...` path
+    /// for the browser. This takes precedence over the browser being opened, so can be used to
+    /// open any installed app, including ones which have been moved elsewhere. If no app with this
+    /// bundle id is installed, opening fails with an error of kind [ErrorKind::NotFound].
+    ///
+    /// This is honoured only on macOS.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use webbrowser::{open_browser_with_options, Browser, BrowserOptions};
+    ///
+    /// let mut options = BrowserOptions::new();
+    /// options.with_macos_bundle_id("org.mozilla.firefox");
+    /// let _ = open_browser_with_options(Browser::Default, "https://example.com", &options);
+    /// ```
+    pub fn with_macos_bundle_id(&mut self, bundle_id: &str) -> &mut Self {
+        self.macos_bundle_id = Some(bundle_id.to_owned());
+        self
+    }
+
+    /// Reports the time `elapsed` in opening a url to the timing sink, if any
+    #[cfg_attr(target_family = "wasm", allow(dead_code))]
+    fn report_timing(&self, elapsed: std::time::Duration) {
+        if let Some(SharedTimingSink(sink)) = &self.timing_sink {
+            let mut sink = sink.lock().unwrap_or_else(|err| err.into_inner());
+            sink(elapsed);
+        }
+    }
+
+    /// Returns the names of the executables to search for, when opening `browser`
+    #[cfg_attr(
+        not(all(
+            unix,
+            not(any(
+                target_os = "ios",
+                target_os = "tvos",
+                target_os = "visionos",
+                target_os = "android",
+                target_family = "wasm",
+            ))
+        )),
+        allow(dead_code)
+    )]
+    fn binary_names(&self, browser: Browser) -> Vec<&str> {
+        match self.binary_overrides.iter().find(|(b, _)| *b == browser) {
+            Some((_, binaries)) => binaries.iter().map(String::as_str).collect(),
+            None => match browser {
+                Browser::Firefox => vec!["firefox"],
+                Browser::Chrome => vec!["google-chrome", "google-chrome-stable", "chrome"],
+                Browser::Opera => vec!["opera"],
+                Browser::WebPositive => vec!["WebPositive"],
+                Browser::Brave => vec!["brave-browser", "brave"],
+                Browser::Edge => vec!["microsoft-edge", "microsoft-edge-stable", "msedge"],
+                Browser::TorBrowser => {
+                    vec!["torbrowser-launcher", "org.torproject.torbrowser-launcher"]
+                }
+                Browser::Epiphany => vec!["epiphany", "org.gnome.Epiphany"],
+                Browser::Konqueror => vec!["konqueror"],
+                Browser::Default | Browser::InternetExplorer | Browser::Safari => Vec::new(),
+            },
+        }
+    }
+
+    /// Specify how urls with a scheme other than `http`, `https` or `file` (e.g. `mailto:` or
+    /// `myapp://`) get handled. See [UnknownSchemePolicy] for the options available. If this isn't
+    /// specified, such urls go through the same platform specific resolution as http urls, except
+    /// for `ftp://` urls, which are handed over to the OS default handler (e.g. a file manager or
+    /// an FTP client), as if [UnknownSchemePolicy::OsHandler] had been specified. Modern browsers
+    /// have dropped support for FTP, so opening such urls in them fails anyway.
+    ///
+    /// Pages of browser extensions, i.e. `chrome-extension://` and `moz-extension://` urls, are
+    /// always forced into the browser, as if [UnknownSchemePolicy::Browser] had been specified,
+    /// unless [UnknownSchemePolicy::Reject] is. They can be opened only in browsers of the
+    /// matching [BrowserFamily], i.e. Chromium and Firefox respectively, failing with an error of
+    /// kind [ErrorKind::Unsupported] otherwise, e.g. if the default browser is Safari.
+    ///
+    /// If the `hardened` feature is enabled, such urls are always rejected, irrespective of this
+    /// policy.
+    pub fn with_unknown_scheme_policy(&mut self, policy: UnknownSchemePolicy) -> &mut Self {
+        self.unknown_scheme_policy = Some(policy);
+        self
+    }
+
+    /// Allow `mailto:`, `tel:` and `sms:` urls to be handed over to the OS default handler for
+    /// their scheme (e.g. a mail client), as if [UnknownSchemePolicy::OsHandler] had been specified
+    /// for them, irrespective of the [UnknownSchemePolicy] otherwise in effect. This is disabled by
+    /// default, so that urls are only ever opened in a browser, unless asked otherwise. As with
+    /// [UnknownSchemePolicy::OsHandler], this is honoured only with [Browser::Default].
+    ///
+    /// If the `hardened` feature is enabled, such urls are always rejected, irrespective of this.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use webbrowser::{open_browser_with_options, Browser, BrowserOptions};
+    ///
+    /// let mut options = BrowserOptions::new();
+    /// options.with_allow_non_browser_schemes(true);
+    /// let _ = open_browser_with_options(Browser::Default, "mailto:a@example.com", &options);
+    /// ```
+    pub fn with_allow_non_browser_schemes(&mut self, allow: bool) -> &mut Self {
+        self.allow_non_browser_schemes = allow;
+        self
+    }
+
+    /// Returns the [UnknownSchemePolicy] which applies to `target`, if it has an unknown scheme
+    fn unknown_scheme_policy(&self, target: &TargetType) -> Option<UnknownSchemePolicy> {
+        match target.0.scheme() {
+            "http" | "https" | "file" => None,
+            "mailto" | "tel" | "sms" if self.allow_non_browser_schemes => {
+                Some(UnknownSchemePolicy::OsHandler)
+            }
+            // browsers no longer handle ftp, so unless told otherwise, we don't force it into one
+            "ftp" => self
+                .unknown_scheme_policy
+                .or(Some(UnknownSchemePolicy::OsHandler)),
+            // extension pages exist only within the browser, unless rejected altogether
+            "chrome-extension" | "moz-extension" => match self.unknown_scheme_policy {
+                Some(UnknownSchemePolicy::Reject) => Some(UnknownSchemePolicy::Reject),
+                _ => Some(UnknownSchemePolicy::Browser),
+            },
+            _ => self.unknown_scheme_policy,
+        }
+    }
+
+    /// Open urls with the specified `scheme` (e.g. `mailto`) in `browser`, overriding the browser
+    /// passed to [open_browser_with_options] and friends. E.g. to open web urls in Chrome, while
+    /// leaving `mailto:` urls to the OS default handler:
+    ///
+    /// ```no_run
+    /// use webbrowser::{open_browser_with_options, Browser, BrowserOptions};
+    ///
+    /// let mut options = BrowserOptions::new();
+    /// options
+    ///     .with_scheme_browser("http", Browser::Chrome)
+    ///     .with_scheme_browser("https", Browser::Chrome);
+    /// let _ = open_browser_with_options(Browser::Default, "mailto:a@example.com", &options);
+    /// ```
+    ///
+    /// Schemes are matched case insensitively, against the url after any upgrade via
+    /// [BrowserOptions::with_https_upgrade], so `http` and `https` need to be specified separately.
+    /// Urls with other schemes are opened in the browser passed in. The
+    /// [UnknownSchemePolicy] still applies first, so urls it rejects are never opened. Specifying
+    /// a browser for the same scheme again replaces the earlier one.
+    pub fn with_scheme_browser(&mut self, scheme: &str, browser: Browser) -> &mut Self {
+        let scheme = scheme.to_ascii_lowercase();
+        match self
+            .scheme_browsers
+            .iter_mut()
+            .find(|(existing, _)| *existing == scheme)
+        {
+            Some((_, existing)) => *existing = browser,
+            None => self.scheme_browsers.push((scheme, browser)),
+        }
+        self
+    }
+
+    /// Returns the browser to open `target` in, when `browser` has been asked for
+    fn browser_for(&self, browser: Browser, target: &TargetType) -> Browser {
+        self.scheme_browsers
+            .iter()
+            .find(|(scheme, _)| scheme == target.0.scheme())
+            .map(|(_, browser)| *browser)
+            .unwrap_or(browser)
+    }
+
+    /// Validate the options, irrespective of whether they're honoured on this platform
+    fn validate(&self) -> Result<()> {
+        if let Some(display) = &self.display {
+            if display_env_var(display).is_none() {
+                return Err(io::Error::new(ErrorKind::InvalidInput, "invalid display"));
+            }
+        }
+        if let Some((_, _, width, height)) = self.window_bounds {
+            if width == 0 || height == 0 {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "invalid window size",
+                ));
+            }
+        }
+        if let Some(app_id) = &self.flatpak_app {
+            if app_id.is_empty()
+                || app_id.starts_with('-')
+                || !app_id
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '.' | '_' | '-'))
+            {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "invalid flatpak app id",
+                ));
+            }
+        }
+        if let Some((root, cmd_path, powershell_path)) = &self.wsl_windows_config {
+            if !root.is_absolute()
+                || !cmd_path.is_absolute()
+                || !powershell_path
+                    .as_ref()
+                    .map_or(true, |path| path.is_absolute())
+            {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "invalid wsl windows config",
+                ));
+            }
+        }
+        if let Some(bundle_id) = &self.macos_bundle_id {
+            if bundle_id.is_empty()
+                || !bundle_id
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '.' | '-'))
+            {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "invalid macos bundle id",
+                ));
+            }
+        }
+        if let Some(wrapper) = &self.wrapper_command {
+            if wrapper.trim().is_empty() {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "empty wrapper command",
+                ));
+            }
+        }
+        if let Some(properties) = &self.systemd_scope {
+            for (name, value) in properties {
+                if name.is_empty()
+                    || !name.chars().all(|ch| ch.is_ascii_alphanumeric())
+                    || value.trim().is_empty()
+                {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidInput,
+                        "invalid systemd scope property",
+                    ));
+                }
+            }
+        }
+        if let Some(cache_dir) = &self.cache_dir {
+            // the dir is created only when launching, so we just check that it can be, i.e. that
+            // its closest existing ancestor is a directory
+            let existing = cache_dir
+                .ancestors()
+                .filter(|path| !path.as_os_str().is_empty())
+                .find(|path| path.exists());
+            if existing.map_or(false, |path| !path.is_dir()) {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "cache dir is not a directory, and can't be created",
+                ));
+            }
+        }
+        if let Some(timezone) = &self.timezone {
+            if timezone.trim().is_empty() {
+                return Err(io::Error::new(ErrorKind::InvalidInput, "empty timezone"));
+            }
+        }
+        if let Some(profile) = &self.profile {
+            if profile.trim().is_empty() {
+                return Err(io::Error::new(ErrorKind::InvalidInput, "empty profile"));
+            }
+        }
+        for lang in &self.accept_languages {
+            if lang.is_empty()
+                || !lang
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+            {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "invalid accept language",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How urls with an unknown scheme, i.e. other than `http`, `https` or `file`, are handled. See
+/// [BrowserOptions::with_unknown_scheme_policy].
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+#[non_exhaustive]
+pub enum UnknownSchemePolicy {
+    /// Reject the url with an error of kind [ErrorKind::InvalidInput], without opening anything
+    Reject,
+    /// Hand the url over to the OS default handler for its scheme, e.g. a mail client for
+    /// `mailto:` urls. This is honoured only with [Browser::Default], and is how urls are opened
+    /// on Android and iOS anyway. On the other platforms, it means:
+    /// * **Linux/\*BSD** - the desktop specific helpers like `kde-open` or `gio open` are used,
+    ///   followed by `xdg-open`, skipping `$BROWSER` and the default browser. As xdg-utils older
+    ///   than 1.1 don't use the `x-scheme-handler` associations, `xdg-open` and the xdg default
+    ///   browser are skipped for such urls if `xdg-open --version` reports an older version.
+    ///   This is probed once per `xdg-open` binary (and user it's run as) for the process
+    ///   lifetime, and an unknown version is assumed to be recent enough.
+    /// * **macOS** - the app registered for the scheme with Launch Services is used.
+    /// * **Windows** - the command registered for the scheme in the registry is used.
+    OsHandler,
+    /// Force the url into the default browser, even if some other app is registered as the
+    /// handler for its scheme. On Linux/\*BSD, this means only `$BROWSER`, the xdg default
+    /// browser, and `x-www-browser` are used, skipping the desktop specific helpers. This is how
+    /// urls are opened on macOS and Windows, when no policy is specified. This has no effect on
+    /// Android, iOS and wasm, where only http(s) urls are supported for now.
+    Browser,
+}
+
+/// A callback for the time taken to open a url, as set via [BrowserOptions::with_timing_sink]
+pub type TimingSink = Box<dyn FnMut(std::time::Duration) + Send>;
+
+/// A [TimingSink], shared between clones of [BrowserOptions], which are compared by identity
+#[derive(Clone)]
+struct SharedTimingSink(std::sync::Arc<std::sync::Mutex<TimingSink>>);
+
+impl fmt::Debug for SharedTimingSink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("TimingSink")
+    }
+}
+
+impl PartialEq for SharedTimingSink {
+    fn eq(&self, other: &Self) -> bool {
+        std::sync::Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SharedTimingSink {}
+
+impl std::hash::Hash for SharedTimingSink {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::ptr::hash(&*self.0, state)
+    }
+}
+
+/// The display server a browser runs on natively. See [BrowserOptions::with_display_platform].
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+#[non_exhaustive]
+pub enum DisplayPlatform {
+    /// Wayland
+    Wayland,
+    /// X11, which on a Wayland desktop means XWayland
+    X11,
+}
+
+/// Returns the env var corresponding to the `display`, i.e. `DISPLAY` for X11 displays like
+/// `:0` or `host:0.1`, and `WAYLAND_DISPLAY` for Wayland displays like `wayland-0`. Returns
+/// `None` if `display` doesn't look valid.
+pub(crate) fn display_env_var(display: &str) -> Option<&'static str> {
+    if let Some(idx) = display.rfind(':') {
+        // X11 display of the form [host]:display[.screen]
+        let is_number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        let mut parts = display[idx + 1..].splitn(2, '.');
+        if parts.all(is_number) {
+            Some("DISPLAY")
+        } else {
+            None
+        }
+    } else if !display.is_empty() && !display.contains(char::is_whitespace) {
+        // Wayland display, which is a socket name or an absolute path
+        Some("WAYLAND_DISPLAY")
+    } else {
+        None
+    }
 }
 
 /// Opens the URL on the default browser of this platform
@@ -249,6 +1496,9 @@ impl BrowserOptions {
 /// * `hardened` feature is enabled, and the URL was not a valid http(s) url, say a `file:///`
 /// * On ios/android/wasm, if the url is not a valid http(s) url
 ///
+/// If the url points to a local `.webloc` (macOS) or `.url` (Windows) shortcut file, the url
+/// embedded in the shortcut is opened instead, on all platforms.
+///
 /// Equivalent to:
 /// ```no_run
 /// # use webbrowser::{Browser, open_browser};
@@ -302,41 +1552,1290 @@ pub fn open_browser_with_options(
     url: &str,
     options: &BrowserOptions,
 ) -> Result<()> {
-    let target = TargetType::try_from(url)?;
-
-    // if feature:hardened is enabled, make sure we accept only HTTP(S) URLs
-    #[cfg(feature = "hardened")]
-    if !target.is_http() {
-        return Err(Error::new(
-            ErrorKind::InvalidInput,
-            "only http/https urls allowed",
-        ));
-    }
+    open_browser_with_outcome(browser, url, options).map(|_| ())
+}
 
-    if cfg!(any(
-        target_os = "ios",
-        target_os = "tvos",
+/// Opens the specified URL on the specific browser (if available) requested, with the specified
+/// options, like [open_browser_with_options], but returning a typed [Error] on failure, for
+/// callers which need to tell apart the causes of failure.
+///
+/// # Examples
+/// ```no_run
+/// use webbrowser::{Browser, BrowserOptions, Error};
+///
+/// match webbrowser::open_browser_detailed(Browser::Default, "https://example.com", &BrowserOptions::new()) {
+///     Ok(()) => {}
+///     Err(Error::BrowserNotFound(_)) => println!("please install a browser"),
+///     Err(err) => println!("failed to open: {err}"),
+/// }
+/// ```
+pub fn open_browser_detailed(
+    browser: Browser,
+    url: &str,
+    options: &BrowserOptions,
+) -> std::result::Result<(), Error> {
+    open_browser_with_options(browser, url, options).map_err(Error::from)
+}
+
+/// Opens the specified URL on the default browser, with the options configured by `configure`,
+/// which is passed the default options to modify. This avoids having to hold on to a
+/// [BrowserOptions] just to call its builder methods.
+///
+/// Return semantics are the same as for [open](fn.open.html).
+///
+/// # Examples
+/// ```no_run
+/// if webbrowser::open_with_builder("http://github.com", |options| {
+///     options.with_suppress_output(false).with_https_upgrade(true);
+/// })
+/// .is_ok()
+/// {
+///     // ...
+/// }
+/// ```
+pub fn open_with_builder<F>(url: &str, configure: F) -> Result<()>
+where
+    F: FnOnce(&mut BrowserOptions),
+{
+    let mut options = BrowserOptions::default();
+    configure(&mut options);
+    open_browser_with_options(Browser::Default, url, &options)
+}
+
+/// Opens the specified URL on the specific browser (if available) requested, with the specified
+/// options, returning details of how the URL was opened. This allows callers to verify that a
+/// browser was indeed opened, e.g. by rejecting [HandlerKind::OsDefault] outcomes.
+///
+/// Error semantics are the same as for [open](fn.open.html).
+///
+/// # Examples
+/// ```no_run
+/// use webbrowser::{open_browser_with_outcome, Browser, BrowserOptions, HandlerKind};
+///
+/// let outcome = open_browser_with_outcome(Browser::Default, "http://github.com", &BrowserOptions::new());
+/// if let Ok(outcome) = outcome {
+///     if outcome.handler_kind() == HandlerKind::OsDefault {
+///         // the url may have been opened in a non-browser app
+///     }
+/// }
+/// ```
+pub fn open_browser_with_outcome(
+    browser: Browser,
+    url: &str,
+    options: &BrowserOptions,
+) -> Result<OpenOutcome> {
+    #[cfg(not(target_family = "wasm"))]
+    let start = std::time::Instant::now();
+    let (browser, target) = prepare_target(browser, url, options)?;
+    let options = options_for(browser, options);
+
+    let result = if cfg!(any(
+        target_os = "ios",
+        target_os = "tvos",
         target_os = "visionos",
         target_os = "macos",
         target_os = "android",
         target_family = "wasm",
         windows,
         unix,
-    )) {
-        os::open_browser_internal(browser, &target, options)
-    } else {
-        Err(Error::new(ErrorKind::NotFound, "unsupported platform"))
+    )) {
+        os::open_browser_internal(browser, &target, &options)
+    } else {
+        Err(io::Error::new(ErrorKind::NotFound, "unsupported platform"))
+    };
+    #[cfg(not(target_family = "wasm"))]
+    options.report_timing(start.elapsed());
+    result
+}
+
+/// Opens the specified URL via the specified `command`, for browsers which aren't covered by
+/// [Browser], e.g. `vivaldi`. The `command` is interpreted as follows:
+/// * **Linux/\*bsd** - the name of an executable, which is searched for in `PATH`, or its path
+/// * **Windows** - the name of an executable, which is searched for by the OS, or its path
+/// * **macOS** - the bundle id of an app (e.g. `com.vivaldi.Vivaldi`), the path of an app bundle,
+///   or the path of an executable
+/// * **All other platforms** - an error of kind [ErrorKind::Unsupported] is returned
+///
+/// The URL is passed to the command as its last argument, following any
+/// [BrowserOptions::with_args], and other browser specific flags if the browser can be recognised
+/// from the command, e.g. for a private window. The URL gets checked as per `options` as usual,
+/// but options which select the browser, e.g. [BrowserOptions::with_scheme_browser], don't apply.
+///
+/// # Examples
+/// ```no_run
+/// use webbrowser::{open_with_command, BrowserOptions};
+///
+/// if open_with_command("vivaldi", "http://github.com", &BrowserOptions::new()).is_ok() {
+///     // ...
+/// }
+/// ```
+pub fn open_with_command(command: &str, url: &str, options: &BrowserOptions) -> Result<()> {
+    #[cfg(not(target_family = "wasm"))]
+    let start = std::time::Instant::now();
+    let target = prepare_url(url, options)?;
+    let result = os::open_with_command_internal(command, &target, options);
+    #[cfg(not(target_family = "wasm"))]
+    options.report_timing(start.elapsed());
+    result.map(|_| ())
+}
+
+/// Validates `options`, and parses `url` into the target to be opened as per them, returning it
+/// along with the browser to open it in
+fn prepare_target(
+    browser: Browser,
+    url: &str,
+    options: &BrowserOptions,
+) -> Result<(Browser, TargetType)> {
+    let target = prepare_url(url, options)?;
+    let browser = options.browser_for(browser, &target);
+    check_extension_family(&target, || browser.family())?;
+    let target = target.for_browser(browser, options)?;
+    Ok((browser, target))
+}
+
+/// Validates `options`, and parses `url` into the target to be opened as per them, irrespective
+/// of the browser
+fn prepare_url(url: &str, options: &BrowserOptions) -> Result<TargetType> {
+    options.validate()?;
+    let mut target = TargetType::try_from(url)?;
+    if options.raw_url {
+        target.set_raw(url);
+    }
+    if options.https_upgrade {
+        target.upgrade_to_https()?;
+    }
+    #[cfg(not(target_family = "wasm"))]
+    if options.verify_file_exists {
+        target.verify_file_exists()?;
+    }
+    if options.unknown_scheme_policy(&target) == Some(UnknownSchemePolicy::Reject) {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "unknown url scheme",
+        ));
+    }
+
+    // if feature:hardened is enabled, make sure we accept only HTTP(S) URLs
+    #[cfg(feature = "hardened")]
+    if !target.is_http() {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "only http/https urls allowed",
+        ));
+    }
+    Ok(target)
+}
+
+/// Fails if `target` is the page of a browser extension, which isn't available in browsers of
+/// `family`, as extensions are specific to the browser family they're built for. The `family` is
+/// resolved only for such urls, as it may take running commands for the default browser.
+fn check_extension_family(
+    target: &TargetType,
+    family: impl FnOnce() -> BrowserFamily,
+) -> Result<()> {
+    let (scheme, expected) = match target.0.scheme() {
+        "chrome-extension" => ("chrome-extension", BrowserFamily::Chromium),
+        "moz-extension" => ("moz-extension", BrowserFamily::Firefox),
+        _ => return Ok(()),
+    };
+    if family() == expected {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            ErrorKind::Unsupported,
+            format!("{scheme} urls can be opened only in {expected:?} based browsers"),
+        ))
+    }
+}
+
+/// Returns the `options` to be used for opening a url in `browser`
+fn options_for(browser: Browser, options: &BrowserOptions) -> std::borrow::Cow<'_, BrowserOptions> {
+    // the profile, kiosk mode & extra args can't be safely added to the command of the default
+    // browser
+    if browser == Browser::Default
+        && (options.profile.is_some() || options.kiosk || !options.args.is_empty())
+    {
+        let mut opts = options.clone();
+        opts.profile = None;
+        opts.kiosk = false;
+        opts.args.clear();
+        std::borrow::Cow::Owned(opts)
+    } else {
+        std::borrow::Cow::Borrowed(options)
+    }
+}
+
+/// Opens the specified URL on the specific browser (if available) requested, with the specified
+/// options, returning the pid of the launched browser process, where it can be determined.
+///
+/// The pid is available only when we launch the browser process ourselves, i.e.
+/// * **Linux/\*BSD** - when the browser is launched via `$BROWSER`, the xdg desktop entry of the
+///   default browser, or `x-www-browser`. It isn't available when the URL is handed over to a
+///   helper like `xdg-open` or `kde-open`, as the helper's pid isn't that of the browser.
+/// * **macOS** - only when the browser is launched as a binary, e.g. as installed via Homebrew
+/// * **Windows** - when the default browser is launched via its registered command, i.e. when
+///   `options` need control over the browser process (e.g. [BrowserOptions::with_new_window]), or
+///   the URL is a local file. Otherwise, the URL is handed over to the shell via `ShellExecuteW`,
+///   which doesn't tell us the pid.
+///
+/// `None` is returned in all other cases, including on Android, iOS and wasm, and on dry runs.
+/// Note that the pid may be that of a short lived process, if the browser was already running,
+/// and the URL got handed over to the existing instance.
+///
+/// # Examples
+/// ```no_run
+/// use webbrowser::{open_browser_pid, Browser, BrowserOptions};
+///
+/// if let Ok(Some(pid)) = open_browser_pid(Browser::Default, "http://github.com", &BrowserOptions::new()) {
+///     // ...
+/// }
+/// ```
+pub fn open_browser_pid(
+    browser: Browser,
+    url: &str,
+    options: &BrowserOptions,
+) -> Result<Option<u32>> {
+    open_browser_with_outcome(browser, url, options).map(|outcome| outcome.pid())
+}
+
+/// The kind of handler which was used to open a URL, as reported by [OpenOutcome::handler_kind]
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+#[non_exhaustive]
+pub enum HandlerKind {
+    /// A GUI web browser was launched directly, e.g. via `$BROWSER` or the xdg desktop entry of the
+    /// default browser on Linux, or via the default browser app on macOS and Windows
+    Browser,
+    /// The URL was handed over to an OS level handler, e.g. `kde-open`, `gio` or `xdg-open` on
+    /// Linux, or the OS on Android/iOS. This is expected to open a browser, but that can't be
+    /// confirmed, as the handler may pick a non-browser app.
+    OsDefault,
+    /// A text browser (e.g. `lynx`) was run in the foreground
+    TextBrowser,
+    /// The URL was handed over to the XDG desktop portal, e.g. when running under Flatpak
+    Portal,
+}
+
+/// Families of browsers which share the same engine, and hence command line flags & capabilities,
+/// as returned by [classify_default]
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+#[non_exhaustive]
+pub enum BrowserFamily {
+    /// Chrome, and other browsers based on Chromium, e.g. Edge, Brave, Vivaldi & Opera
+    Chromium,
+    /// Firefox, and browsers derived from it, e.g. LibreWolf & Waterfox
+    Firefox,
+    /// Safari, and other browsers based on WebKit, e.g. GNOME Web (Epiphany) & WebPositive
+    WebKit,
+    /// A text browser, e.g. `lynx` or `w3m`
+    Text,
+    /// The browser couldn't be identified
+    Unknown,
+}
+
+impl BrowserFamily {
+    /// Classifies a browser by `name`, which can be the path of its binary or app bundle, its
+    /// desktop entry (e.g. `org.mozilla.firefox.desktop`), or its bundle id (e.g.
+    /// `com.google.Chrome`). Only the last path component is considered, and matched piece-wise,
+    /// so that e.g. `google-chrome-stable` or `Google Chrome.app` is recognised as Chromium.
+    #[cfg_attr(
+        any(
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "visionos",
+            target_os = "android",
+            target_family = "wasm",
+        ),
+        allow(dead_code)
+    )]
+    pub(crate) fn from_name(name: &str) -> Self {
+        let name = name
+            .trim_end_matches(['/', '\\'])
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let pieces: Vec<&str> = name
+            .split(['.', '-', '_', ' '])
+            .filter(|piece| !piece.is_empty())
+            .collect();
+        let has_prefix = |prefixes: &[&str]| {
+            pieces
+                .iter()
+                .any(|piece| prefixes.iter().any(|prefix| piece.starts_with(prefix)))
+        };
+        if pieces.iter().any(|piece| TEXT_BROWSERS.contains(piece)) {
+            BrowserFamily::Text
+        } else if has_prefix(&["firefox", "iceweasel", "librewolf", "waterfox"]) {
+            BrowserFamily::Firefox
+        } else if has_prefix(&[
+            "chrome", "chromium", "brave", "msedge", "edge", "vivaldi", "opera",
+        ]) {
+            BrowserFamily::Chromium
+        } else if has_prefix(&["safari", "epiphany", "webpositive", "luakit", "surf"]) {
+            BrowserFamily::WebKit
+        } else {
+            BrowserFamily::Unknown
+        }
+    }
+}
+
+impl BrowserFamily {
+    /// Returns the argument which makes browsers of this family open a private window
+    #[cfg_attr(
+        any(
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "visionos",
+            target_os = "android",
+            target_family = "wasm",
+        ),
+        allow(dead_code)
+    )]
+    pub(crate) fn incognito_arg(self) -> Option<&'static str> {
+        match self {
+            BrowserFamily::Chromium => Some("--incognito"),
+            BrowserFamily::Firefox => Some("-private-window"),
+            _ => None,
+        }
+    }
+}
+
+/// Known text browsers, which run in the terminal
+#[cfg_attr(
+    any(
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "visionos",
+        target_os = "android",
+        target_family = "wasm",
+    ),
+    allow(dead_code)
+)]
+pub(crate) static TEXT_BROWSERS: [&str; 9] = [
+    "lynx", "links", "links2", "elinks", "w3m", "eww", "netrik", "retawq", "curl",
+];
+
+/// Returns the [BrowserFamily] of the default browser, e.g. to check beforehand if options
+/// specific to Chromium or Firefox would take effect. This doesn't launch the browser.
+///
+/// Platform specific behaviour is as follows:
+/// * **Linux/\*BSD** - the first command in `$BROWSER` is classified if set, else the default
+///   browser as per `xdg-settings get default-web-browser`
+/// * **macOS** - the app registered for `https` urls is classified
+/// * **Windows** - the command registered for `https` urls is classified, or the one for `http`
+///   urls, if none is registered for `https`
+/// * **All other platforms** - [BrowserFamily::Unknown] is returned
+///
+/// # Examples
+/// ```no_run
+/// use webbrowser::BrowserFamily;
+///
+/// if webbrowser::classify_default() == BrowserFamily::Chromium {
+///     // ...
+/// }
+/// ```
+pub fn classify_default() -> BrowserFamily {
+    os::default_browser_family()
+}
+
+/// Returns the name of the default browser, without launching it, e.g. for showing it in a
+/// settings UI. The form of the name depends on the platform, as follows:
+/// * **Linux/\*BSD** - the first command in `$BROWSER` if set, e.g. `firefox`, else the desktop
+///   entry as per `xdg-settings get default-web-browser`, e.g. `firefox.desktop`
+/// * **macOS** - the name of the app registered for `https` urls, e.g. `Firefox`
+/// * **Windows** - the executable name in the command registered for `https` urls, or for `http`
+///   urls, if none is registered for `https`, e.g. `firefox.exe`
+/// * **All other platforms** - an error of kind [ErrorKind::Unsupported] is returned
+///
+/// An error of kind [ErrorKind::NotFound] is returned if the default browser can't be determined.
+///
+/// # Examples
+/// ```no_run
+/// if let Ok(name) = webbrowser::default_browser_name() {
+///     println!("default browser: {name}");
+/// }
+/// ```
+pub fn default_browser_name() -> Result<String> {
+    os::default_browser_name()
+}
+
+/// Details of how a URL was opened, as returned by [open_browser_with_outcome]
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct OpenOutcome {
+    handler_kind: HandlerKind,
+    pid: Option<u32>,
+}
+
+impl OpenOutcome {
+    pub(crate) fn new(handler_kind: HandlerKind, pid: Option<u32>) -> Self {
+        OpenOutcome { handler_kind, pid }
+    }
+
+    /// The kind of handler used to open the URL. On a dry run, this is the kind of handler
+    /// which would've been used.
+    pub fn handler_kind(&self) -> HandlerKind {
+        self.handler_kind
+    }
+
+    /// The pid of the launched browser process, if it could be determined. See [open_browser_pid]
+    /// for when this is available.
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+}
+
+/// Works out the command which would be run to open the specified URL on the specific browser (if
+/// available) requested, with the specified options, without running it. This goes through the
+/// same resolution as [open_browser_with_options], e.g. the fallback chain on linux/*bsd, so the
+/// result is what would be run if the URL were opened right now, e.g. for displaying which
+/// browser it will open in, or for tests. Platform specific behaviour is as follows:
+/// * **Linux/\*bsd & Windows** - the executable, along with all of its arguments, including any
+///   browser specific flags as per `options`, and prefixes like the wrapper command or `sudo`. An
+///   error of kind [ErrorKind::NotFound] is returned if the URL would be opened without running a
+///   command, e.g. via D-Bus.
+/// * **macOS** - the app bundle and the URL, if the URL is to be opened via Launch Services, which
+///   doesn't take any arguments. If the browser's binary is to be run instead, e.g. for
+///   [BrowserOptions::with_args], it's the binary with its arguments.
+/// * **All other platforms** - an error of kind [ErrorKind::Unsupported] is returned
+///
+/// # Examples
+/// ```no_run
+/// use webbrowser::{resolve, Browser, BrowserOptions};
+///
+/// if let Ok(resolved) = resolve(Browser::Default, "https://example.com", &BrowserOptions::new()) {
+///     println!("will open in {}", resolved.program().display());
+/// }
+/// ```
+pub fn resolve(browser: Browser, url: &str, options: &BrowserOptions) -> Result<ResolvedTarget> {
+    let (browser, target) = prepare_target(browser, url, options)?;
+    let mut options = options_for(browser, options).into_owned();
+    options.dry_run = true;
+    os::resolve_internal(browser, &target, &options)
+}
+
+/// The command which would be run to open a URL, as returned by [resolve]
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct ResolvedTarget {
+    program: std::path::PathBuf,
+    args: Vec<std::ffi::OsString>,
+}
+
+impl ResolvedTarget {
+    #[cfg_attr(
+        any(
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "visionos",
+            target_os = "android",
+            target_family = "wasm",
+        ),
+        allow(dead_code)
+    )]
+    pub(crate) fn from_command(cmd: &std::process::Command) -> Self {
+        ResolvedTarget {
+            program: cmd.get_program().into(),
+            args: cmd.get_args().map(|arg| arg.to_owned()).collect(),
+        }
+    }
+
+    /// The executable which would be run, or the app bundle which would be launched on macOS. This
+    /// is as it'd be passed to the OS, so it may not be an absolute path, e.g. for `sudo`.
+    pub fn program(&self) -> &std::path::Path {
+        &self.program
+    }
+
+    /// The arguments it would be run with, excluding the program itself
+    pub fn args(&self) -> &[std::ffi::OsString] {
+        &self.args
+    }
+}
+
+/// Opens the specified URL on the specific browser (if available) requested, with the specified
+/// options, additionally returning a record of each means of opening the URL which was attempted,
+/// in order, for diagnosing why a URL didn't open as expected.
+///
+/// On linux/*bsd, each step of the fallback chain (see [open](fn.open.html)) gets a record, and
+/// for specific browsers, each of the binary names searched for. Steps which aren't reached, as
+/// an earlier one succeeded, aren't recorded. On all other platforms, a single record is returned
+/// for the platform's means of opening the URL.
+///
+/// # Examples
+/// ```no_run
+/// use webbrowser::{open_browser_traced, Browser, BrowserOptions};
+///
+/// let (result, attempts) = open_browser_traced(Browser::Default, "http://github.com", &BrowserOptions::new());
+/// if result.is_err() {
+///     for attempt in attempts {
+///         eprintln!("{}: {:?} {:?}", attempt.method(), attempt.outcome(), attempt.error());
+///     }
+/// }
+/// ```
+pub fn open_browser_traced(
+    browser: Browser,
+    url: &str,
+    options: &BrowserOptions,
+) -> (Result<()>, Vec<AttemptRecord>) {
+    ATTEMPTS.with(|attempts| *attempts.borrow_mut() = Some(Vec::new()));
+    let result = open_browser_with_options(browser, url, options);
+    let mut attempts = ATTEMPTS
+        .with(|attempts| attempts.borrow_mut().take())
+        .unwrap_or_default();
+    if attempts.is_empty() {
+        // either the platform doesn't record its attempts, or we failed before getting to them
+        attempts.push(AttemptRecord::new(&browser.to_string(), &result));
+    }
+    (result, attempts)
+}
+
+/// Opens `url` in `browser` like [open_browser_with_options], additionally returning the handle
+/// of the spawned browser process, e.g. for waiting on it, or killing it after a timeout.
+///
+/// `None` is returned when no process is spawned by us, which is the case on wasm, iOS, Android,
+/// and on macOS when the browser is launched via Launch Services, or when the browser runs in the
+/// foreground, as text browsers do. Note that the process may well be a helper like `xdg-open`,
+/// or a browser which hands the url over to an already running instance, and exits right away.
+///
+/// # Examples
+/// ```no_run
+/// use webbrowser::{Browser, BrowserOptions};
+///
+/// if let Ok(Some(mut child)) =
+///     webbrowser::open_browser_with_handle(Browser::Default, "https://example.com", &BrowserOptions::new())
+/// {
+///     let _ = child.kill();
+/// }
+/// ```
+pub fn open_browser_with_handle(
+    browser: Browser,
+    url: &str,
+    options: &BrowserOptions,
+) -> Result<Option<std::process::Child>> {
+    let (result, child) = with_child_capture(|| open_browser_with_options(browser, url, options));
+    result.map(|_| child)
+}
+
+/// Runs `op`, capturing the handle of the browser process spawned by it, if any
+fn with_child_capture<T>(op: impl FnOnce() -> T) -> (T, Option<std::process::Child>) {
+    CHILD.with(|child| *child.borrow_mut() = Some(None));
+    let result = op();
+    let child = CHILD.with(|child| child.borrow_mut().take()).flatten();
+    (result, child)
+}
+
+/// Retains the handle of a spawned browser process, if [open_browser_with_handle] is in progress
+#[cfg_attr(
+    any(
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "visionos",
+        target_os = "android",
+        target_family = "wasm",
+    ),
+    allow(dead_code)
+)]
+pub(crate) fn retain_child(spawned: std::process::Child) {
+    CHILD.with(|child| {
+        if let Some(child) = child.borrow_mut().as_mut() {
+            *child = Some(spawned);
+        }
+    });
+}
+
+thread_local! {
+    /// The browser process spawned on this thread, if being captured
+    static CHILD: std::cell::RefCell<Option<Option<std::process::Child>>> = const { std::cell::RefCell::new(None) };
+
+    /// The attempts made while opening a URL on this thread, if being traced
+    static ATTEMPTS: std::cell::RefCell<Option<Vec<AttemptRecord>>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Records an attempt of opening a URL via `method`, if [open_browser_traced] is in progress
+#[cfg_attr(
+    not(all(
+        unix,
+        not(any(
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "visionos",
+            target_os = "macos",
+            target_os = "android",
+            target_family = "wasm",
+        ))
+    )),
+    allow(dead_code)
+)]
+pub(crate) fn record_attempt<T>(method: &str, result: &Result<T>) {
+    ATTEMPTS.with(|attempts| {
+        if let Some(attempts) = attempts.borrow_mut().as_mut() {
+            attempts.push(AttemptRecord::new(method, result));
+        }
+    });
+}
+
+/// The outcome of an attempt, as reported by [AttemptRecord::outcome]
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+#[non_exhaustive]
+pub enum AttemptOutcome {
+    /// The URL was opened
+    Succeeded,
+    /// The means of opening the URL isn't available, e.g. the command wasn't found
+    Unavailable,
+    /// The means of opening the URL is available, but failed
+    Failed,
+}
+
+/// An attempt made at opening a URL, as returned by [open_browser_traced]
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct AttemptRecord {
+    method: String,
+    outcome: AttemptOutcome,
+    error: Option<String>,
+}
+
+impl AttemptRecord {
+    fn new<T>(method: &str, result: &Result<T>) -> Self {
+        let (outcome, error) = match result {
+            Ok(_) => (AttemptOutcome::Succeeded, None),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                (AttemptOutcome::Unavailable, Some(err.to_string()))
+            }
+            Err(err) => (AttemptOutcome::Failed, Some(err.to_string())),
+        };
+        AttemptRecord {
+            method: method.to_owned(),
+            outcome,
+            error,
+        }
+    }
+
+    /// The means via which opening the URL was attempted, e.g. `$BROWSER` or `xdg-open`
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// The outcome of the attempt. Note that the distinction between [AttemptOutcome::Unavailable]
+    /// and [AttemptOutcome::Failed] is best-effort, as it's based on the kind of error reported.
+    pub fn outcome(&self) -> AttemptOutcome {
+        self.outcome
+    }
+
+    /// The error reported by the attempt, if it didn't succeed
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+/// Opens each of `urls` in a new window of the default browser, e.g. for viewing them side by
+/// side. The result of opening each url is returned, in the same order as `urls`.
+///
+/// Platform specific behaviour is as follows:
+/// * **macOS** - a new instance of the browser is requested, similar to `open -n`
+/// * **Linux/\*BSD/Windows** - `-new-window` or `--new-window` is passed to the browser, if the
+///   default browser command is recognised as Firefox or Chromium based. Otherwise, e.g. if the url
+///   is opened via `xdg-open`, the browser's own behaviour applies (typically a new tab).
+/// * **All other platforms** - the browser's own behaviour applies
+///
+/// # Examples
+/// ```no_run
+/// let results = webbrowser::open_windows(&["https://example.com/a", "https://example.com/b"]);
+/// if results.iter().all(|result| result.is_ok()) {
+///     // ...
+/// }
+/// ```
+pub fn open_windows(urls: &[&str]) -> Vec<Result<()>> {
+    let mut options = BrowserOptions::new();
+    options.with_new_window(true);
+    urls.iter()
+        .map(|url| open_browser_with_options(Browser::Default, url, &options))
+        .collect()
+}
+
+/// Opens all of `urls` in the default browser, e.g. as tabs. A url which fails to open doesn't
+/// stop the rest from being opened, and if any of them fail, the error returned lists each of
+/// them, with the reason for the failure. The [ErrorKind] of the error is that of the first
+/// failure.
+///
+/// On macOS, the urls are handed over to the browser in a single launch, if they're all to be
+/// opened in the same app. In that case, if the launch fails, all of them are reported as failed.
+/// On all other platforms, they're opened one by one, in order.
+///
+/// # Examples
+/// ```no_run
+/// if let Err(err) = webbrowser::open_all(&["https://example.com/a", "https://example.com/b"]) {
+///     eprintln!("{err}");
+/// }
+/// ```
+pub fn open_all(urls: &[&str]) -> Result<()> {
+    open_all_with_options(Browser::Default, urls, &BrowserOptions::default())
+}
+
+/// Opens all of `urls` on the specific browser (if available) requested, with the specified
+/// options. Error semantics are the same as for [open_all].
+///
+/// # Examples
+/// ```no_run
+/// use webbrowser::{open_all_with_options, Browser, BrowserOptions};
+///
+/// let urls = ["https://example.com/a", "https://example.com/b"];
+/// if open_all_with_options(Browser::Firefox, &urls, &BrowserOptions::new()).is_ok() {
+///     // ...
+/// }
+/// ```
+pub fn open_all_with_options(
+    browser: Browser,
+    urls: &[&str],
+    options: &BrowserOptions,
+) -> Result<()> {
+    #[cfg(target_os = "macos")]
+    if let Some(result) = open_all_in_one_launch(browser, urls, options) {
+        return result;
+    }
+
+    let failures = urls
+        .iter()
+        .filter_map(|url| {
+            open_browser_with_options(browser, url, options)
+                .err()
+                .map(|err| (*url, err))
+        })
+        .collect();
+    all_opened(urls.len(), failures)
+}
+
+/// Opens all of `urls` in a single instance of the Chromium based `browser`, i.e. [Browser::Chrome],
+/// [Browser::Edge], [Browser::Brave] or [Browser::Opera], by passing all of them to a single
+/// invocation of the browser, which opens them as tabs, in order. If the browser is already
+/// running with the same user data dir (i.e. `--user-data-dir`, which is the user's usual one,
+/// unless passed via [BrowserOptions::with_args]), the invocation just hands the urls over to the
+/// running instance, and exits. Compared to [open_all], this avoids spawning a browser process
+/// per url, which adds up when opening many urls, e.g. in load tests.
+///
+/// Only Chromium based browsers are supported, as they're known to take multiple urls on the
+/// command line, and an error of kind [ErrorKind::Unsupported] is returned for all others,
+/// including [Browser::Default], whose command may well be a helper like `xdg-open`. Each url is
+/// checked as per `options` as usual, and if any of them fails the checks, or is to be opened in
+/// another browser as per [BrowserOptions::with_scheme_browser], nothing gets opened. Note that
+/// the command line length is limited by the OS, so very large numbers of urls need to be split
+/// into batches.
+///
+/// The urls are passed as extra arguments, so this works wherever [BrowserOptions::with_args] is
+/// honoured for the browser, i.e. on linux/*bsd, on macOS (where the browser's binary is run
+/// directly, instead of via Launch Services), and on Windows for Brave & Edge.
+///
+/// # Examples
+/// ```no_run
+/// use webbrowser::{open_all_in_one_instance, Browser, BrowserOptions};
+///
+/// let urls = ["https://example.com/a", "https://example.com/b"];
+/// if open_all_in_one_instance(Browser::Chrome, &urls, &BrowserOptions::new()).is_ok() {
+///     // ...
+/// }
+/// ```
+pub fn open_all_in_one_instance(
+    browser: Browser,
+    urls: &[&str],
+    options: &BrowserOptions,
+) -> Result<OpenOutcome> {
+    let (url, options) = one_instance_options(browser, urls, options)?;
+    open_browser_with_outcome(browser, url, &options)
+}
+
+/// Returns the url to be opened in `browser`, and the options to do so with, for opening all of
+/// `urls` in a single invocation, with all but the last url passed as extra arguments
+fn one_instance_options<'a>(
+    browser: Browser,
+    urls: &[&'a str],
+    options: &BrowserOptions,
+) -> Result<(&'a str, BrowserOptions)> {
+    if !matches!(
+        browser,
+        Browser::Chrome | Browser::Edge | Browser::Brave | Browser::Opera
+    ) {
+        return Err(io::Error::new(
+            ErrorKind::Unsupported,
+            format!("{browser} can't open multiple urls in one instance"),
+        ));
+    }
+    let (url, others) = urls
+        .split_last()
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "no urls to open"))?;
+    let mut options = options.clone();
+    for other in others {
+        let (other_browser, target) = prepare_target(browser, other, &options)?;
+        if other_browser != browser {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("{other} is to be opened in {other_browser}"),
+            ));
+        }
+        options.args.push((&target as &str).to_owned());
+    }
+    Ok((url, options))
+}
+
+/// Opens all of `urls` in a single launch, returning `None` if they don't all go to the same app
+#[cfg(target_os = "macos")]
+fn open_all_in_one_launch(
+    browser: Browser,
+    urls: &[&str],
+    options: &BrowserOptions,
+) -> Option<Result<()>> {
+    let start = std::time::Instant::now();
+    let mut failures = Vec::new();
+    let mut targets = Vec::new();
+    let mut launch_browser = None;
+    for url in urls {
+        match prepare_target(browser, url, options) {
+            Ok((browser, target)) => {
+                if *launch_browser.get_or_insert(browser) != browser {
+                    return None;
+                }
+                targets.push((*url, target));
+            }
+            Err(err) => failures.push((*url, err)),
+        }
+    }
+    let browser = launch_browser?;
+    let target_refs: Vec<&TargetType> = targets.iter().map(|(_, target)| target).collect();
+    let result = os::open_all_internal(browser, &target_refs, &options_for(browser, options))?;
+    options.report_timing(start.elapsed());
+    if let Err(err) = result {
+        // the urls get opened together, so they fail together
+        failures.extend(
+            targets
+                .iter()
+                .map(|(url, _)| (*url, io::Error::new(err.kind(), err.to_string()))),
+        );
+    }
+    Some(all_opened(urls.len(), failures))
+}
+
+/// Returns the result of opening `count` urls, with an error listing the `failures`, if any
+fn all_opened(count: usize, failures: Vec<(&str, io::Error)>) -> Result<()> {
+    match failures.first() {
+        None => Ok(()),
+        Some((_, first)) => {
+            let details: Vec<String> = failures
+                .iter()
+                .map(|(url, err)| format!("{url}: {err}"))
+                .collect();
+            Err(io::Error::new(
+                first.kind(),
+                format!(
+                    "failed to open {} of {} urls ({})",
+                    failures.len(),
+                    count,
+                    details.join("; ")
+                ),
+            ))
+        }
+    }
+}
+
+/// Opens `url` in the default browser, with the
+/// [DevTools protocol](https://chromedevtools.github.io/devtools-protocol/) enabled on `port`, via
+/// `--remote-debugging-port=<port>`, e.g. for driving the browser in automation. See
+/// [open_browser_with_debugging] for details.
+///
+/// # Examples
+/// ```no_run
+/// if let Ok(endpoint) = webbrowser::open_with_debugging("http://github.com", 9222) {
+///     // connect a DevTools client via the endpoint ...
+/// }
+/// ```
+pub fn open_with_debugging(url: &str, port: u16) -> Result<String> {
+    open_browser_with_debugging(Browser::Default, url, port)
+}
+
+/// Opens `url` in the specified browser, with the
+/// [DevTools protocol](https://chromedevtools.github.io/devtools-protocol/) enabled on `port`, via
+/// `--remote-debugging-port=<port>`, e.g. for driving the browser in automation.
+///
+/// This works only for Chromium based browsers. An error of kind [ErrorKind::Unsupported] is
+/// returned without opening the url, if `browser` isn't one, or for [Browser::Default], if the
+/// default browser isn't one as per [classify_default]. As the flag can only be passed to a
+/// browser which we launch ourselves, only Chromium based browser commands are run, while the
+/// others, and helpers like `xdg-open`, are skipped, failing with the same error if no Chromium
+/// based browser could be launched. It's supported only on Linux/\*BSD & Windows, and an error of
+/// kind [ErrorKind::Unsupported] is returned on all other platforms. Note that if the browser is
+/// already running, the url may get opened in the existing instance, without the flag taking
+/// effect.
+///
+/// On success, the url of the DevTools endpoint describing the browser is returned, i.e.
+/// `http://127.0.0.1:<port>/json/version`, whose `webSocketDebuggerUrl` a DevTools client can
+/// connect to. Note that the browser may take a moment to start listening on it.
+///
+/// # Examples
+/// ```no_run
+/// use webbrowser::Browser;
+///
+/// if let Ok(endpoint) = webbrowser::open_browser_with_debugging(Browser::Chrome, "http://github.com", 9222) {
+///     // connect a DevTools client via the endpoint ...
+/// }
+/// ```
+pub fn open_browser_with_debugging(browser: Browser, url: &str, port: u16) -> Result<String> {
+    if cfg!(any(
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "visionos",
+        target_os = "macos",
+        target_os = "android",
+        target_family = "wasm",
+    )) {
+        return Err(io::Error::new(
+            ErrorKind::Unsupported,
+            "remote debugging not supported on this platform",
+        ));
+    }
+    if browser.family() != BrowserFamily::Chromium {
+        return Err(io::Error::new(
+            ErrorKind::Unsupported,
+            "remote debugging is supported only for Chromium based browsers",
+        ));
+    }
+    let mut options = BrowserOptions::new();
+    options.remote_debugging_port = Some(port);
+    open_browser_with_options(browser, url, &options)?;
+    Ok(debugging_endpoint(port))
+}
+
+/// Returns the url of the DevTools endpoint of a browser listening on the debugging `port`
+fn debugging_endpoint(port: u16) -> String {
+    format!("http://127.0.0.1:{port}/json/version")
+}
+
+/// A hook for transforming browser commands before they're run, as set via [set_command_hook]
+pub type CommandHook = Box<dyn Fn(&mut std::process::Command) + Send + Sync>;
+
+/// Sets a global `hook`, which gets to modify every browser command right before it's run, e.g.
+/// to add arguments or environment variables, or to wrap it in a sandbox like `firejail`. This is
+/// the lowest level escape hatch, and is applied after everything else specified in
+/// [BrowserOptions], replacing any previously set hook.
+///
+/// The hook is stored behind a [std::sync::RwLock], so it can be set from any thread, and gets
+/// invoked on the thread opening the url. Note that it applies only where we run commands
+/// ourselves, i.e. on linux/*bsd and windows, and on macOS when the browser is installed as a
+/// binary instead of an app bundle. Helpers like `xdg-open` are subject to it too.
+///
+/// # Examples
+/// ```no_run
+/// webbrowser::set_command_hook(Box::new(|cmd| {
+///     cmd.env("MOZ_ENABLE_WAYLAND", "1");
+/// }));
+/// ```
+pub fn set_command_hook(hook: CommandHook) {
+    match command_hook().write() {
+        Ok(mut guard) => *guard = Some(hook),
+        Err(poisoned) => *poisoned.into_inner() = Some(hook),
+    }
+}
+
+/// Returns true if a hook has been set via [set_command_hook]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn has_command_hook() -> bool {
+    match command_hook().read() {
+        Ok(guard) => guard.is_some(),
+        Err(poisoned) => poisoned.into_inner().is_some(),
+    }
+}
+
+/// Applies the hook set via [set_command_hook], if any, to `cmd`
+#[cfg_attr(
+    any(
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "visionos",
+        target_os = "android",
+        target_family = "wasm",
+    ),
+    allow(dead_code)
+)]
+pub(crate) fn apply_command_hook(cmd: &mut std::process::Command) {
+    let guard = match command_hook().read() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if let Some(hook) = guard.as_ref() {
+        hook(cmd);
+    }
+}
+
+/// Returns the lock holding the command hook
+fn command_hook() -> &'static std::sync::RwLock<Option<CommandHook>> {
+    static HOOK: Global<std::sync::RwLock<Option<CommandHook>>> = Global::new();
+    HOOK.get(|| std::sync::RwLock::new(None))
+}
+
+/// A global value, which gets allocated on first use, and is never deallocated. This is needed
+/// for globals like locks, as their constructors aren't const in our MSRV.
+pub(crate) struct Global<T>(std::sync::atomic::AtomicPtr<T>);
+
+impl<T> Global<T> {
+    pub(crate) const fn new() -> Self {
+        Global(std::sync::atomic::AtomicPtr::new(std::ptr::null_mut()))
+    }
+}
+
+impl<T: Send + Sync> Global<T> {
+    /// Returns the value, allocating it via `init` on first use
+    pub(crate) fn get(&'static self, init: impl FnOnce() -> T) -> &'static T {
+        use std::sync::atomic::Ordering;
+        let mut ptr = self.0.load(Ordering::Acquire);
+        if ptr.is_null() {
+            let new = Box::into_raw(Box::new(init()));
+            ptr = match self.0.compare_exchange(
+                std::ptr::null_mut(),
+                new,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => new,
+                Err(existing) => {
+                    // another thread got there first
+                    drop(unsafe { Box::from_raw(new) });
+                    existing
+                }
+            };
+        }
+        // the value is never deallocated once published
+        unsafe { &*ptr }
+    }
+}
+
+/// Enables or disables caching of the lookups done to find the default browser, e.g. the output
+/// of `xdg-settings` on linux/*bsd, or the commands registered for `https` urls on windows, so that
+/// opening many urls in a row doesn't repeat them. Caching is disabled by default, and disabling
+/// it also clears the cache.
+///
+/// Changes to the default browser aren't picked up while the cache is in use, unless it's cleared
+/// via [clear_default_browser_cache]. This has no effect on other platforms.
+///
+/// # Examples
+/// ```no_run
+/// webbrowser::cache_default_browser(true);
+/// for page in 1..=10 {
+///     let _ = webbrowser::open(&format!("https://example.com/{page}"));
+/// }
+/// ```
+pub fn cache_default_browser(enabled: bool) {
+    CACHE_ENABLED.store(enabled, std::sync::atomic::Ordering::Release);
+    if !enabled {
+        clear_default_browser_cache();
+    }
+}
+
+/// Clears the lookups cached since [cache_default_browser] was enabled, e.g. after the default
+/// browser has been changed
+pub fn clear_default_browser_cache() {
+    match lookup_cache().lock() {
+        Ok(mut cache) => cache.clear(),
+        Err(poisoned) => poisoned.into_inner().clear(),
+    }
+}
+
+/// Whether lookups are being cached, as per [cache_default_browser]
+static CACHE_ENABLED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Returns the result of `lookup`, cached under `key` if [cache_default_browser] is enabled.
+/// Failed lookups aren't cached.
+#[cfg_attr(
+    any(
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "visionos",
+        target_os = "macos",
+        target_os = "android",
+        target_family = "wasm",
+    ),
+    allow(dead_code)
+)]
+pub(crate) fn cached_lookup(key: &str, lookup: impl FnOnce() -> Result<String>) -> Result<String> {
+    if !CACHE_ENABLED.load(std::sync::atomic::Ordering::Acquire) {
+        return lookup();
+    }
+    let cached = match lookup_cache().lock() {
+        Ok(cache) => cache.get(key).cloned(),
+        Err(poisoned) => poisoned.into_inner().get(key).cloned(),
+    };
+    if let Some(value) = cached {
+        return Ok(value);
+    }
+    let value = lookup()?;
+    if let Ok(mut cache) = lookup_cache().lock() {
+        cache.insert(key.to_owned(), value.clone());
+    }
+    Ok(value)
+}
+
+/// Returns the lock holding the cached lookups
+fn lookup_cache() -> &'static std::sync::Mutex<std::collections::HashMap<String, String>> {
+    static CACHE: Global<std::sync::Mutex<std::collections::HashMap<String, String>>> =
+        Global::new();
+    CACHE.get(|| std::sync::Mutex::new(std::collections::HashMap::new()))
+}
+
+/// Opens `primary`, typically a deep link into a native app (e.g. `myapp://path`), if the
+/// platform has a handler registered for it, else opens `fallback` in the default browser.
+///
+/// Platform specific behaviour for `primary` is as follows:
+/// * **Android** - an `ACTION_VIEW` intent is created for `primary`, and is started only if
+///   `resolveActivity()` finds an activity to handle it. Note that on Android 11+, package
+///   visibility rules require the target app/scheme to be declared under `<queries>` in your
+///   manifest.
+/// * **iOS/tvOS/visionOS** - `primary` is opened only if `canOpenURL:` returns true. Note that the
+///   scheme needs to be declared under `LSApplicationQueriesSchemes` in your `Info.plist`.
+/// * **All other platforms** - there's no reliable way to determine whether `primary` would end up
+///   in a non-browser app, so `fallback` is opened directly.
+///
+/// If the `hardened` feature is enabled, `primary` is never attempted, as it's not an http(s) url.
+///
+/// # Examples
+/// ```no_run
+/// if webbrowser::open_with_fallback("myapp://item/42", "https://example.com/item/42").is_ok() {
+///     // ...
+/// }
+/// ```
+pub fn open_with_fallback(primary: &str, fallback: &str) -> Result<()> {
+    #[cfg(all(
+        not(feature = "hardened"),
+        any(
+            target_os = "android",
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "visionos"
+        )
+    ))]
+    match TargetType::try_from(primary)
+        .and_then(|target| os::open_deep_link(&target, &BrowserOptions::default()))
+    {
+        Ok(()) => return Ok(()),
+        Err(err) => log::debug!("unable to open {}, using fallback: {}", primary, err),
+    }
+
+    #[cfg(not(all(
+        not(feature = "hardened"),
+        any(
+            target_os = "android",
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "visionos"
+        )
+    )))]
+    log::debug!("deep links not supported, using fallback for {}", primary);
+
+    open(fallback)
+}
+
+/// Opens `url` in whichever browser works, for when it doesn't matter which one it is. The
+/// browsers are tried in the following order, until one of them succeeds:
+/// 1. [Browser::Default], i.e. the same as [open]
+/// 2. each of the browsers detected in the system, as returned by [Browser::installed], i.e. in
+///    the order of [Browser::all]
+///
+/// Browsers which aren't installed are not attempted at all.
+///
+/// If all of them fail, an error listing each attempt is returned. An invalid `url` is reported
+/// right away, without trying any other browsers.
+///
+/// # Examples
+/// ```no_run
+/// if webbrowser::open_best_effort("https://example.com").is_ok() {
+///     // ...
+/// }
+/// ```
+pub fn open_best_effort(url: &str) -> Result<()> {
+    let options = BrowserOptions::default();
+    let mut attempts = Vec::new();
+    let browsers = std::iter::once(Browser::Default).chain(Browser::installed());
+    for browser in browsers {
+        match open_browser_with_options(browser, url, &options) {
+            Ok(()) => return Ok(()),
+            Err(err) if err.kind() == ErrorKind::InvalidInput => return Err(err),
+            Err(err) => {
+                log::debug!("unable to open {} in {}: {}", url, browser, err);
+                attempts.push(format!("{browser}: {err}"));
+            }
+        }
+    }
+    Err(io::Error::new(
+        ErrorKind::NotFound,
+        format!("no browser could open the url ({})", attempts.join("; ")),
+    ))
+}
+
+/// Reveals the specified local file or directory in the file manager, i.e. opens the directory
+/// containing it, with the item selected. Relative paths are resolved against the current
+/// directory, and an error of kind [ErrorKind::NotFound] is returned if `path` doesn't exist.
+///
+/// Platform specific behaviour is as follows:
+/// * **Linux/\*BSD** - if the `dbus` feature is enabled, the `ShowItems` method of the
+///   [org.freedesktop.FileManager1](https://www.freedesktop.org/wiki/Specifications/file-manager-interface/)
+///   D-Bus interface is used. If that isn't available, the parent directory is opened via
+///   `xdg-open`, without the item being selected.
+/// * **All other platforms** - not supported yet, and an error of kind [ErrorKind::Unsupported]
+///   is returned.
+///
+/// If the `hardened` feature is enabled, this always returns an error of kind
+/// [ErrorKind::Unsupported], as local files are not allowed.
+///
+/// # Examples
+/// ```no_run
+/// if webbrowser::reveal_in_file_manager("target/report.html").is_ok() {
+///     // ...
+/// }
+/// ```
+pub fn reveal_in_file_manager(path: &str) -> Result<()> {
+    #[cfg(all(
+        not(feature = "hardened"),
+        unix,
+        not(any(
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "visionos",
+            target_os = "macos",
+            target_os = "android",
+            target_family = "wasm",
+        ))
+    ))]
+    {
+        if !std::path::Path::new(path).exists() {
+            return Err(io::Error::new(ErrorKind::NotFound, "path not found"));
+        }
+        let target = TargetType::from_file_path(path)?;
+        os::reveal_in_file_manager(&target, &BrowserOptions::default())
+    }
+
+    #[cfg(not(all(
+        not(feature = "hardened"),
+        unix,
+        not(any(
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "visionos",
+            target_os = "macos",
+            target_os = "android",
+            target_family = "wasm",
+        ))
+    )))]
+    {
+        log::debug!("revealing {} in file manager not supported", path);
+        Err(io::Error::new(
+            ErrorKind::Unsupported,
+            "revealing in file manager not supported on this platform",
+        ))
     }
 }
 
 /// The link we're trying to open, represented as a URL. Local files get represented
-/// via `file://...` URLs
-struct TargetType(url::Url);
+/// via `file://...` URLs. If set, the second field holds the (minimally quoted) original
+/// url, which gets passed to the browser instead of the normalized one.
+struct TargetType(url::Url, Option<String>);
 
 impl TargetType {
     /// Returns true if this target represents an HTTP url, false otherwise
     #[cfg(any(
         feature = "hardened",
+        windows,
         target_os = "android",
         target_os = "ios",
         target_os = "tvos",
@@ -360,7 +2859,113 @@ impl TargetType {
         if self.is_http() {
             Ok(self.0.as_str())
         } else {
-            Err(Error::new(ErrorKind::InvalidInput, "not an http url"))
+            Err(io::Error::new(ErrorKind::InvalidInput, "not an http url"))
+        }
+    }
+
+    /// If this target is a local `.webloc`/`.url` shortcut file, returns the target it points to
+    #[cfg(not(target_family = "wasm"))]
+    fn follow_shortcut(self) -> Result<Self> {
+        if self.0.scheme() == "file" {
+            if let Ok(path) = self.0.to_file_path() {
+                if let Some(url) = shortcut::get_shortcut_url(&path)? {
+                    return url::Url::parse(&url).map(|u| Self(u, None)).map_err(|_| {
+                        io::Error::new(ErrorKind::InvalidData, "invalid url in shortcut file")
+                    });
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Apply any browser specific transformations requested in `options`
+    fn for_browser(self, browser: Browser, options: &BrowserOptions) -> Result<Self> {
+        match &options.firefox_container {
+            Some(container) if browser == Browser::Firefox => self.in_firefox_container(container),
+            _ => Ok(self),
+        }
+    }
+
+    /// Returns the `ext+container:` url which opens this target in the Firefox `container`
+    fn in_firefox_container(&self, container: &str) -> Result<Self> {
+        let params = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("name", container)
+            .append_pair("url", self)
+            .finish();
+        url::Url::parse(&format!("ext+container:{params}"))
+            .map(|u| Self(u, None))
+            .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "invalid firefox container url"))
+    }
+
+    /// Use `raw`, the original form of this url, when passing it to the browser. This is
+    /// ignored if `raw` isn't a url, e.g. if it's a local file path.
+    ///
+    /// An internationalized host is still passed in its punycode form, as launchers like
+    /// `cmd.exe` may mangle non-ascii arguments, and it means the same to the browser anyway.
+    fn set_raw(&mut self, raw: &str) {
+        // on windows, a path like C:\abc.html parses as a url with scheme "c"
+        if matches!(url::Url::parse(raw), Ok(u) if u.scheme() == self.0.scheme()) {
+            let mut raw = quote_raw_url(raw);
+            if let (Some(range), Some(host)) = (raw_host_range(&raw), self.0.host_str()) {
+                if !raw[range.clone()].is_ascii() {
+                    raw.replace_range(range, host);
+                }
+            }
+            self.1 = Some(raw);
+        }
+    }
+
+    /// Rewrites an `http` url to `https`, unless it's for a loopback host
+    fn upgrade_to_https(&mut self) -> Result<()> {
+        if self.0.scheme() != "http" || self.is_loopback() {
+            return Ok(());
+        }
+        let rest = &self.0.as_str()[4..];
+        self.0 = url::Url::parse(&format!("https{rest}")).map_err(|_| {
+            io::Error::new(ErrorKind::InvalidInput, "failed to upgrade url to https")
+        })?;
+        if let Some(raw) = &self.1 {
+            self.1 = match raw.get(..5) {
+                Some(prefix) if prefix.eq_ignore_ascii_case("http:") => {
+                    Some(format!("https{}", &raw[4..]))
+                }
+                // fall back to the normalized url, as we can't safely rewrite this one
+                _ => None,
+            };
+        }
+        Ok(())
+    }
+
+    /// Returns true if the host of this url is a loopback one
+    fn is_loopback(&self) -> bool {
+        match self.0.host() {
+            Some(url::Host::Domain(domain)) => {
+                let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+                domain == "localhost" || domain.ends_with(".localhost")
+            }
+            Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+            Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+            None => false,
+        }
+    }
+
+    /// If this target is a `file://` url, returns an error if the file it points to doesn't exist
+    #[cfg(not(target_family = "wasm"))]
+    fn verify_file_exists(&self) -> Result<()> {
+        if self.0.scheme() != "file" {
+            return Ok(());
+        }
+        let path = self
+            .0
+            .to_file_path()
+            .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "not a local file url"))?;
+        if path.exists() {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                ErrorKind::NotFound,
+                format!("file not found: {}", path.display()),
+            ))
         }
     }
 
@@ -372,9 +2977,9 @@ impl TargetType {
         } else {
             pb
         })
-        .map_err(|_| Error::new(ErrorKind::InvalidInput, "failed to convert path to url"))?;
+        .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "failed to convert path to url"))?;
 
-        Ok(Self(url))
+        Ok(Self(url, None))
     }
 }
 
@@ -382,7 +2987,7 @@ impl Deref for TargetType {
     type Target = str;
 
     fn deref(&self) -> &Self::Target {
-        self.0.as_str()
+        self.1.as_deref().unwrap_or_else(|| self.0.as_str())
     }
 }
 
@@ -393,28 +2998,88 @@ impl Display for TargetType {
 }
 
 impl std::convert::TryFrom<&str> for TargetType {
-    type Error = Error;
+    type Error = io::Error;
 
     #[cfg(target_family = "wasm")]
     fn try_from(value: &str) -> Result<Self> {
         url::Url::parse(value)
-            .map(|u| Ok(Self(u)))
-            .map_err(|_| Error::new(ErrorKind::InvalidInput, "invalid url for wasm"))?
+            .map(|u| Ok(Self(u, None)))
+            .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "invalid url for wasm"))?
     }
 
     #[cfg(not(target_family = "wasm"))]
     fn try_from(value: &str) -> Result<Self> {
-        match url::Url::parse(value) {
+        let target = match url::Url::parse(value) {
             Ok(u) => {
                 if u.scheme().len() == 1 && cfg!(windows) {
                     // this can happen in windows that C:\abc.html gets parsed as scheme "C"
                     Self::from_file_path(value)
                 } else {
-                    Ok(Self(u))
+                    Ok(Self(u, None))
+                }
+            }
+            Err(err) => {
+                if has_url_scheme(value) {
+                    // this was meant to be a url (e.g. an IPv6 literal with a zone id, which
+                    // isn't supported in urls), so we shouldn't treat it as a local file
+                    Err(io::Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("invalid url: {err}"),
+                    ))
+                } else {
+                    Self::from_file_path(value)
                 }
             }
-            Err(_) => Self::from_file_path(value),
+        };
+        target.and_then(Self::follow_shortcut)
+    }
+}
+
+/// Percent-encode the characters in `raw` which are never valid in a url, and which can
+/// break a command line, leaving everything else untouched
+fn quote_raw_url(raw: &str) -> String {
+    let mut quoted = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        if c.is_ascii_control() || matches!(c, ' ' | '"' | '<' | '>' | '`') {
+            quoted.push_str(&format!("%{:02X}", c as u8));
+        } else {
+            quoted.push(c);
+        }
+    }
+    quoted
+}
+
+/// Returns the byte range of the host within the `raw` url, if it has one
+fn raw_host_range(raw: &str) -> Option<std::ops::Range<usize>> {
+    let start = raw.find("://")? + 3;
+    let end = raw[start..]
+        .find(['/', '?', '#'])
+        .map_or(raw.len(), |idx| start + idx);
+    let host_start = raw[start..end]
+        .rfind('@')
+        .map_or(start, |idx| start + idx + 1);
+    let host_port = &raw[host_start..end];
+    let host_end = match host_port.rfind(':') {
+        // the colons of an IPv6 literal aren't followed by a port
+        Some(idx) if !host_port.ends_with(']') => host_start + idx,
+        _ => end,
+    };
+    Some(host_start..host_end)
+}
+
+/// Returns true if `value` starts with what looks like a url scheme followed by `://`
+#[cfg(not(target_family = "wasm"))]
+fn has_url_scheme(value: &str) -> bool {
+    match value.find("://") {
+        // single char schemes are excluded, as on windows, they're drive letters
+        Some(idx) if idx > 1 => {
+            let scheme = &value[..idx];
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
         }
+        _ => false,
     }
 }
 
@@ -441,3 +3106,618 @@ fn test_open_safari() {
 fn test_open_webpositive() {
     assert!(open_browser(Browser::WebPositive, "http://github.com").is_ok());
 }
+
+#[test]
+fn test_ipv6_literal_loopback() {
+    let target = TargetType::try_from("http://[::1]:8080/").expect("failed to parse url");
+    assert_eq!(&target as &str, "http://[::1]:8080/");
+}
+
+#[test]
+fn test_ipv6_literal_full() {
+    let target = TargetType::try_from("http://[2001:db8:85a3::8a2e:370:7334]:8080/path?q=1")
+        .expect("failed to parse url");
+    assert_eq!(
+        &target as &str,
+        "http://[2001:db8:85a3::8a2e:370:7334]:8080/path?q=1"
+    );
+}
+
+#[test]
+fn test_ipv6_literal_zone_id() {
+    // zone ids aren't supported in urls, but we should error out instead of
+    // treating the url as a local file
+    if let Err(err) = TargetType::try_from("http://[fe80::1%eth0]/") {
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    } else {
+        panic!("expected url with ipv6 zone id to fail");
+    }
+}
+
+#[test]
+fn test_display_env_var() {
+    assert_eq!(display_env_var(":0"), Some("DISPLAY"));
+    assert_eq!(display_env_var("localhost:10.1"), Some("DISPLAY"));
+    assert_eq!(display_env_var("wayland-1"), Some("WAYLAND_DISPLAY"));
+    assert_eq!(display_env_var(":"), None);
+    assert_eq!(display_env_var(":0.x"), None);
+    assert_eq!(display_env_var(""), None);
+    assert_eq!(display_env_var("wayland 1"), None);
+}
+
+#[test]
+fn test_https_upgrade() {
+    let upgrade = |url: &str| {
+        let mut target = TargetType::try_from(url).expect("failed to parse url");
+        target.upgrade_to_https().expect("failed to upgrade");
+        target.to_string()
+    };
+    assert_eq!(
+        upgrade("http://example.com:80/a?b=c#d"),
+        "https://example.com/a?b=c#d"
+    );
+    assert_eq!(
+        upgrade("HTTP://Example.com:8080/"),
+        "https://example.com:8080/"
+    );
+    assert_eq!(upgrade("ftp://example.com/"), "ftp://example.com/");
+    for url in [
+        "http://localhost:3000/",
+        "http://app.localhost/",
+        "http://127.0.0.2/",
+        "http://[::1]:8080/",
+    ] {
+        assert_eq!(upgrade(url), url);
+    }
+
+    // raw urls get upgraded too
+    let url = "http://example.com/%7euser";
+    let mut target = TargetType::try_from(url).expect("failed to parse url");
+    target.set_raw(url);
+    target.upgrade_to_https().expect("failed to upgrade");
+    assert_eq!(&*target, "https://example.com/%7euser");
+}
+
+#[test]
+fn test_unknown_scheme_policy_reject() {
+    let mut options = BrowserOptions::new();
+    options
+        .with_unknown_scheme_policy(UnknownSchemePolicy::Reject)
+        .with_dry_run(true);
+    let err = open_browser_with_outcome(Browser::Default, "myapp://item/42", &options)
+        .expect_err("unknown scheme not rejected");
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    let target = TargetType::try_from("https://example.com").expect("failed to parse url");
+    assert_eq!(options.unknown_scheme_policy(&target), None);
+    let target = TargetType::try_from("mailto:a@example.com").expect("failed to parse url");
+    assert_eq!(
+        options.unknown_scheme_policy(&target),
+        Some(UnknownSchemePolicy::Reject)
+    );
+}
+
+#[test]
+fn test_scheme_browser() {
+    let mut options = BrowserOptions::new();
+    options
+        .with_scheme_browser("HTTP", Browser::Chrome)
+        .with_scheme_browser("mailto", Browser::Firefox)
+        .with_scheme_browser("mailto", Browser::Default)
+        .with_dry_run(true);
+    let browser_for = |browser: Browser, url: &str| {
+        let target = TargetType::try_from(url).expect("failed to parse url");
+        options.browser_for(browser, &target)
+    };
+    assert_eq!(
+        browser_for(Browser::Default, "http://example.com"),
+        Browser::Chrome
+    );
+    assert_eq!(
+        browser_for(Browser::Firefox, "http://example.com"),
+        Browser::Chrome
+    );
+    assert_eq!(
+        browser_for(Browser::Chrome, "mailto:a@example.com"),
+        Browser::Default
+    );
+    assert_eq!(
+        browser_for(Browser::Firefox, "https://example.com"),
+        Browser::Firefox
+    );
+
+    // mailto urls end up with the default handler, even if a browser is asked for, and is
+    // available, irrespective of whether the default handler is
+    #[cfg(all(
+        unix,
+        not(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "tvos",
+            target_os = "visionos",
+            target_os = "android",
+            target_family = "wasm",
+        ))
+    ))]
+    {
+        let (dir, firefox) = stub_executable("scheme-browser");
+        let firefox = firefox.to_string_lossy().into_owned();
+        options.with_binary_override(Browser::Firefox, &[&firefox]);
+        let https = open_browser_with_outcome(Browser::Firefox, "https://example.com", &options);
+        let (_, attempts) = open_browser_traced(Browser::Firefox, "mailto:a@example.com", &options);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(https.is_ok(), "stub firefox not used: {:?}", https);
+        assert!(
+            attempts.iter().all(|attempt| attempt.method() != firefox),
+            "mailto not routed to default: {:?}",
+            attempts
+        );
+    }
+}
+
+/// Creates an executable stub named `name` in a dir of its own, returning the dir & the stub, so
+/// that tests can resolve browsers to it via [BrowserOptions::with_binary_override], instead of
+/// depending on the browsers available on the host
+#[cfg(all(
+    test,
+    unix,
+    not(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "visionos",
+        target_os = "android",
+        target_family = "wasm",
+    ))
+))]
+fn stub_executable(name: &str) -> (std::path::PathBuf, std::path::PathBuf) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = std::env::temp_dir().join(format!("webbrowser-test-{}.{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).expect("failed to create stub dir");
+    let path = dir.join(name);
+    std::fs::write(&path, "").expect("failed to create stub");
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+        .expect("failed to set permissions");
+    (dir, path)
+}
+
+#[test]
+fn test_ftp_scheme_policy() {
+    let target =
+        TargetType::try_from("ftp://ftp.example.com/pub/file.txt").expect("failed to parse url");
+    let mut options = BrowserOptions::new();
+    assert_eq!(
+        options.unknown_scheme_policy(&target),
+        Some(UnknownSchemePolicy::OsHandler)
+    );
+    options.with_unknown_scheme_policy(UnknownSchemePolicy::Browser);
+    assert_eq!(
+        options.unknown_scheme_policy(&target),
+        Some(UnknownSchemePolicy::Browser)
+    );
+
+    // other unknown schemes are left as is
+    let target = TargetType::try_from("myapp://item/42").expect("failed to parse url");
+    assert_eq!(BrowserOptions::new().unknown_scheme_policy(&target), None);
+
+    #[cfg(feature = "hardened")]
+    {
+        let err = open_browser_with_options(
+            Browser::Default,
+            "ftp://ftp.example.com/",
+            BrowserOptions::new().with_dry_run(true),
+        )
+        .expect_err("ftp url not rejected");
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn test_allow_non_browser_schemes() {
+    let mut options = BrowserOptions::new();
+    options.with_unknown_scheme_policy(UnknownSchemePolicy::Reject);
+    let policy = |options: &BrowserOptions, url: &str| {
+        options.unknown_scheme_policy(&TargetType::try_from(url).expect("failed to parse url"))
+    };
+    for url in [
+        "mailto:a@example.com",
+        "tel:+15555550100",
+        "sms:+15555550100",
+    ] {
+        assert_eq!(policy(&options, url), Some(UnknownSchemePolicy::Reject));
+    }
+
+    options.with_allow_non_browser_schemes(true);
+    for url in [
+        "mailto:a@example.com",
+        "tel:+15555550100",
+        "sms:+15555550100",
+    ] {
+        assert_eq!(policy(&options, url), Some(UnknownSchemePolicy::OsHandler));
+    }
+    // other schemes are left alone
+    assert_eq!(
+        policy(&options, "myapp://item/42"),
+        Some(UnknownSchemePolicy::Reject)
+    );
+    assert_eq!(policy(&options, "https://example.com"), None);
+
+    #[cfg(feature = "hardened")]
+    {
+        let err = open_browser_with_options(
+            Browser::Default,
+            "mailto:a@example.com",
+            options.with_dry_run(true),
+        )
+        .expect_err("mailto url not rejected");
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
+
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "visionos",
+        target_os = "android",
+        target_family = "wasm",
+    ))
+))]
+#[test]
+fn test_open_all() {
+    // firefox is resolved to a stub, so that valid urls open irrespective of the host browsers
+    let (dir, firefox) = stub_executable("open-all");
+    let mut options = BrowserOptions::new();
+    options
+        .with_dry_run(true)
+        .with_binary_override(Browser::Firefox, &[&firefox.to_string_lossy()]);
+    let empty = open_all_with_options(Browser::Firefox, &[], &options);
+
+    let urls = [
+        "https://example.com/a",
+        "http://[::1",
+        "https://example.com/b",
+        "http://[::2",
+    ];
+    let mixed = open_all_with_options(Browser::Firefox, &urls, &options);
+    let valid = open_all_with_options(Browser::Firefox, &[urls[0], urls[2]], &options);
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert!(empty.is_ok());
+    let err = mixed.expect_err("invalid urls not reported");
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    let msg = err.to_string();
+    assert!(msg.starts_with("failed to open 2 of 4 urls"), "{msg}");
+    assert!(
+        msg.contains("http://[::1: ") && msg.contains("http://[::2: "),
+        "{msg}"
+    );
+    assert!(!msg.contains("example.com"), "{msg}");
+    assert!(valid.is_ok(), "valid urls failed: {:?}", valid);
+}
+
+#[test]
+fn test_debugging_endpoint() {
+    assert_eq!(
+        debugging_endpoint(9222),
+        "http://127.0.0.1:9222/json/version"
+    );
+    let endpoint =
+        TargetType::try_from(debugging_endpoint(65535).as_str()).expect("invalid endpoint url");
+    assert_eq!(endpoint.0.port(), Some(65535));
+}
+
+#[test]
+fn test_open_all_in_one_instance() {
+    let mut options = BrowserOptions::new();
+    options.with_args(&["--user-data-dir=/tmp/load-test"]);
+    let urls = ["http://example.com/a", "https://example.com/b", "https://c"];
+    let (url, opts) =
+        one_instance_options(Browser::Chrome, &urls, &options).expect("failed to prepare");
+    assert_eq!(url, "https://c");
+    assert_eq!(
+        opts.args,
+        [
+            "--user-data-dir=/tmp/load-test",
+            "http://example.com/a",
+            "https://example.com/b"
+        ]
+    );
+
+    // urls are checked as usual
+    options.with_https_upgrade(true);
+    let (_, opts) =
+        one_instance_options(Browser::Edge, &urls, &options).expect("failed to prepare");
+    assert_eq!(opts.args[1], "https://example.com/a");
+    let err = one_instance_options(Browser::Chrome, &["http://[::1", urls[0]], &options)
+        .expect_err("invalid url accepted");
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    options.with_scheme_browser("https", Browser::Firefox);
+    let err = one_instance_options(Browser::Chrome, &urls, &options)
+        .expect_err("url for another browser accepted");
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    for browser in [Browser::Default, Browser::Firefox, Browser::Safari] {
+        let err = open_all_in_one_instance(browser, &urls, &BrowserOptions::new())
+            .expect_err("non-chromium browser accepted");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+    let err = one_instance_options(Browser::Brave, &[], &BrowserOptions::new())
+        .expect_err("no urls accepted");
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn test_timing_sink() {
+    let timings = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink_timings = timings.clone();
+    let mut options = BrowserOptions::new();
+    options
+        .with_dry_run(true)
+        .with_timing_sink(Box::new(move |elapsed| {
+            sink_timings.lock().unwrap().push(elapsed)
+        }));
+
+    // clones share the sink, which gets the timing whether or not a browser is available, so
+    // only the invalid url is expected to fail
+    let cloned = options.clone();
+    assert_eq!(cloned, options);
+    let _ = open_browser_with_options(Browser::Default, "https://example.com", &cloned);
+    assert!(open_browser_with_options(Browser::Default, "http://[::1", &options).is_err());
+    assert_eq!(timings.lock().unwrap().len(), 1);
+    assert_ne!(BrowserOptions::new(), options);
+}
+
+#[test]
+fn test_all_browsers() {
+    for browser in Browser::all() {
+        // every variant round trips via its lowercased name
+        let name = browser.to_string().to_lowercase();
+        assert_eq!(name.parse::<Browser>().as_ref(), Ok(browser));
+
+        // resolution doesn't panic, and any failure is reported as the browser not being found
+        let result = open_browser_with_options(
+            *browser,
+            "https://example.com",
+            BrowserOptions::new().with_dry_run(true),
+        );
+        if let Err(err) = result {
+            assert_eq!(err.kind(), ErrorKind::NotFound, "{browser}: {err}");
+        }
+    }
+}
+
+#[test]
+fn test_browser_family() {
+    for (name, family) in [
+        ("/usr/bin/firefox", BrowserFamily::Firefox),
+        ("firefox-esr", BrowserFamily::Firefox),
+        ("org.mozilla.firefox.desktop", BrowserFamily::Firefox),
+        ("librewolf.desktop", BrowserFamily::Firefox),
+        ("/usr/bin/google-chrome-stable", BrowserFamily::Chromium),
+        ("chromium-browser.desktop", BrowserFamily::Chromium),
+        ("com.google.Chrome", BrowserFamily::Chromium),
+        ("/Applications/Google Chrome.app/", BrowserFamily::Chromium),
+        ("microsoft-edge", BrowserFamily::Chromium),
+        (
+            r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe",
+            BrowserFamily::Chromium,
+        ),
+        ("com.brave.Browser", BrowserFamily::Chromium),
+        ("vivaldi-stable", BrowserFamily::Chromium),
+        ("com.apple.Safari", BrowserFamily::WebKit),
+        ("org.gnome.Epiphany.desktop", BrowserFamily::WebKit),
+        ("/boot/system/apps/WebPositive", BrowserFamily::WebKit),
+        ("/usr/bin/lynx", BrowserFamily::Text),
+        ("w3m", BrowserFamily::Text),
+        (r"C:\tools\links2.exe", BrowserFamily::Text),
+        ("x-www-browser", BrowserFamily::Unknown),
+        ("xdg-open", BrowserFamily::Unknown),
+        ("", BrowserFamily::Unknown),
+    ] {
+        assert_eq!(BrowserFamily::from_name(name), family, "{name}");
+    }
+}
+
+#[test]
+fn test_open_best_effort_invalid_url() {
+    let err = open_best_effort("http://[::1").expect_err("invalid url opened");
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_open_with_builder() {
+    let mut configured = false;
+    let result = open_with_builder("http://[::1", |options| {
+        options.with_dry_run(true);
+        configured = true;
+    });
+    assert!(configured);
+    assert_eq!(
+        result.expect_err("invalid url opened").kind(),
+        ErrorKind::InvalidInput
+    );
+}
+
+#[test]
+fn test_raw_url() {
+    let url = "http://EXAMPLE.com/a/../b/%7euser/x y?q=\"1\"";
+    let mut target = TargetType::try_from(url).expect("failed to parse url");
+    assert_eq!(&*target, "http://example.com/b/%7euser/x%20y?q=%221%22");
+    target.set_raw(url);
+    assert_eq!(
+        &*target,
+        "http://EXAMPLE.com/a/../b/%7euser/x%20y?q=%221%22"
+    );
+    assert_eq!(target.0.host_str(), Some("example.com"));
+}
+
+#[test]
+fn test_detailed_error() {
+    let err = Error::from(io::Error::new(ErrorKind::NotFound, "no browser"));
+    assert!(matches!(&err, Error::BrowserNotFound(msg) if msg == "no browser"));
+    assert_eq!(io::Error::from(err).kind(), ErrorKind::NotFound);
+
+    let err = Error::from(io::Error::new(ErrorKind::PermissionDenied, "denied"));
+    assert!(matches!(&err, Error::LaunchFailed(err) if err.kind() == ErrorKind::PermissionDenied));
+    assert!(error::Error::source(&err).is_some());
+    assert_eq!(io::Error::from(err).kind(), ErrorKind::PermissionDenied);
+
+    // our errors survive a round trip through io::Error
+    let io_err = io::Error::from(Error::PopupBlocked);
+    assert_eq!(io_err.kind(), ErrorKind::Other);
+    assert!(matches!(Error::from(io_err), Error::PopupBlocked));
+
+    let err = open_browser_detailed(Browser::Default, "ftp://[", &BrowserOptions::new())
+        .expect_err("invalid url accepted");
+    assert!(matches!(err, Error::InvalidUrl(_)));
+}
+
+#[test]
+fn test_installed_browsers() {
+    let installed = Browser::installed();
+    assert!(!installed.contains(&Browser::Default));
+    assert!(installed.iter().all(|browser| browser.exists()));
+    // the order of declaration is retained
+    let mut all = Browser::all().iter();
+    assert!(installed.iter().all(|browser| all.any(|b| b == browser)));
+}
+
+#[test]
+fn test_verify_file_exists() {
+    let existing = std::env::current_dir()
+        .expect("failed to get current dir")
+        .join("Cargo.toml");
+    let existing_url = url::Url::from_file_path(&existing).expect("failed to convert path");
+    for target in [existing.to_string_lossy().as_ref(), existing_url.as_str()] {
+        let target = TargetType::try_from(target).expect("failed to parse url");
+        assert!(target.verify_file_exists().is_ok());
+    }
+
+    let missing = existing.with_file_name("does-not-exist.html");
+    let missing_url = url::Url::from_file_path(&missing).expect("failed to convert path");
+    for target in [missing.to_string_lossy().as_ref(), missing_url.as_str()] {
+        let target = TargetType::try_from(target).expect("failed to parse url");
+        let err = target
+            .verify_file_exists()
+            .expect_err("missing file not reported");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    // other urls aren't affected
+    let target = TargetType::try_from("https://example.com").expect("failed to parse url");
+    assert!(target.verify_file_exists().is_ok());
+}
+
+#[test]
+fn test_idn_url() {
+    let url = "http://user@m\u{fc}nchen.de:8080/stra\u{df}e?q=\u{e4}#\u{f6}";
+    let mut target = TargetType::try_from(url).expect("failed to parse url");
+    assert_eq!(
+        &*target,
+        "http://user@xn--mnchen-3ya.de:8080/stra%C3%9Fe?q=%C3%A4#%C3%B6"
+    );
+    // the raw url retains everything but the unicode host
+    target.set_raw(url);
+    assert_eq!(
+        &*target,
+        "http://user@xn--mnchen-3ya.de:8080/stra\u{df}e?q=\u{e4}#\u{f6}"
+    );
+
+    for (raw, expected) in [
+        ("https://M\u{dc}NCHEN.de", "https://xn--mnchen-3ya.de"),
+        ("https://[::1]:8080/\u{e4}", "https://[::1]:8080/\u{e4}"),
+        ("https://EXAMPLE.com/\u{e4}", "https://EXAMPLE.com/\u{e4}"),
+    ] {
+        let mut target = TargetType::try_from(raw).expect("failed to parse url");
+        target.set_raw(raw);
+        assert_eq!(&*target, expected);
+    }
+}
+
+#[test]
+fn test_firefox_container() {
+    let url = "https://example.com/a?b=c&d=e";
+    let mut options = BrowserOptions::new();
+    options.with_firefox_container("Work & Play");
+    for (browser, expected) in [
+        (
+            Browser::Firefox,
+            "ext+container:name=Work+%26+Play&url=https%3A%2F%2Fexample.com%2Fa%3Fb%3Dc%26d%3De",
+        ),
+        (Browser::Chrome, url),
+        (Browser::Default, url),
+    ] {
+        let target = TargetType::try_from(url)
+            .and_then(|target| target.for_browser(browser, &options))
+            .expect("failed to get target");
+        assert_eq!(&*target, expected);
+    }
+}
+
+#[test]
+fn test_extension_urls() {
+    let target = |url: &str| TargetType::try_from(url).expect("failed to parse url");
+    let chrome_url = "chrome-extension://abcdefghijklmnopabcdefghijklmnop/options.html";
+    let moz_url = "moz-extension://1b2c3d4e-5f60-4718-8a9b-0c1d2e3f4a5b/popup.html";
+    let (chrome_ext, moz_ext) = (target(chrome_url), target(moz_url));
+    assert!(check_extension_family(&chrome_ext, || BrowserFamily::Chromium).is_ok());
+    assert!(check_extension_family(&moz_ext, || BrowserFamily::Firefox).is_ok());
+    for (ext, family) in [
+        (&chrome_ext, BrowserFamily::Firefox),
+        (&chrome_ext, BrowserFamily::Unknown),
+        (&moz_ext, BrowserFamily::Chromium),
+        (&moz_ext, BrowserFamily::WebKit),
+    ] {
+        let err = check_extension_family(ext, || family).expect_err("family mismatch accepted");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+    // the family is resolved only for extension urls
+    let https = target("https://example.com");
+    assert!(check_extension_family(&https, || panic!("family resolved")).is_ok());
+
+    // extension urls are forced into the browser, unless rejected
+    let mut options = BrowserOptions::new();
+    options.with_unknown_scheme_policy(UnknownSchemePolicy::OsHandler);
+    for ext in [&chrome_ext, &moz_ext] {
+        assert_eq!(
+            options.unknown_scheme_policy(ext),
+            Some(UnknownSchemePolicy::Browser)
+        );
+    }
+    options.with_unknown_scheme_policy(UnknownSchemePolicy::Reject);
+    assert_eq!(
+        options.unknown_scheme_policy(&chrome_ext),
+        Some(UnknownSchemePolicy::Reject)
+    );
+
+    // specific browsers are checked against their own family
+    let options = BrowserOptions::new();
+    let prepare = |browser: Browser, url: &str| {
+        prepare_target(browser, url, &options).map(|(_, target)| target.0.to_string())
+    };
+    let chrome = prepare(Browser::Chrome, chrome_url);
+    let firefox = prepare(Browser::Firefox, moz_url);
+    let mismatch = prepare(Browser::Firefox, chrome_url);
+    #[cfg(not(feature = "hardened"))]
+    {
+        assert_eq!(chrome.expect("failed to prepare"), chrome_url);
+        assert_eq!(firefox.expect("failed to prepare"), moz_url);
+        let err = mismatch.expect_err("chromium extension opened in firefox");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert!(err.to_string().contains("Chromium"), "{err}");
+    }
+    // they're blocked altogether if hardened
+    #[cfg(feature = "hardened")]
+    for result in [chrome, firefox, mismatch] {
+        assert_eq!(
+            result.expect_err("extension url accepted").kind(),
+            ErrorKind::InvalidInput
+        );
+    }
+}
@@ -1,47 +1,114 @@
-use crate::{Browser, BrowserOptions, Error, ErrorKind, Result, TargetType};
+use crate::{
+    Browser, BrowserFamily, BrowserOptions, ErrorKind, HandlerKind, OpenOutcome, ResolvedTarget,
+    Result, TargetType, UnknownSchemePolicy,
+};
 use core_foundation::array::{CFArray, CFArrayRef};
 use core_foundation::base::TCFType;
+use core_foundation::bundle::CFBundle;
 use core_foundation::error::{CFError, CFErrorRef};
+use core_foundation::string::{CFString, CFStringRef};
 use core_foundation::url::{CFURLRef, CFURL};
+use std::io::Error;
 use std::os::raw::c_void;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// How a url gets opened on macOS
+enum Launch {
+    /// By running the browser binary directly
+    Binary(PathBuf),
+    /// Via Launch Services, in the app bundle at `app_url`, which is the OS default handler for
+    /// the url, rather than a browser, if `os_handler` is set
+    App { app_url: CFURL, os_handler: bool },
+}
 
 /// Deal with opening of browsers on Mac OS X using Core Foundation framework
 pub(super) fn open_browser_internal(
     browser: Browser,
     target: &TargetType,
     options: &BrowserOptions,
-) -> Result<()> {
-    // create the CFUrl for the browser
-    let browser_cf_url = match browser {
-        Browser::Firefox => create_cf_url("file:///Applications/Firefox.app/"),
-        Browser::Chrome => create_cf_url("file:///Applications/Google Chrome.app/"),
-        Browser::Opera => create_cf_url("file:///Applications/Opera.app/"),
-        Browser::Safari => create_cf_url("file:///Applications/Safari.app/"),
-        Browser::Default => {
-            if let Some(dummy_url) = create_cf_url("https://") {
-                let mut err: CFErrorRef = std::ptr::null_mut();
-                let result = unsafe {
-                    LSCopyDefaultApplicationURLForURL(
-                        dummy_url.as_concrete_TypeRef(),
-                        LSROLE_VIEWER,
-                        &mut err,
-                    )
-                };
-                if result.is_null() {
-                    log::error!("failed to get default browser: {}", unsafe {
-                        CFError::wrap_under_create_rule(err)
-                    });
-                    create_cf_url(DEFAULT_BROWSER_URL)
-                } else {
-                    let cf_url = unsafe { CFURL::wrap_under_create_rule(result) };
-                    log::trace!("default browser is {:?}", &cf_url);
-                    Some(cf_url)
+) -> Result<OpenOutcome> {
+    match resolve_launch(browser, target, options)? {
+        Launch::Binary(binary) => open_using_binary(&binary, target, options),
+        Launch::App {
+            app_url,
+            os_handler,
+        } => open_using_launch_services(browser, &app_url, &[target], options, os_handler),
+    }
+}
+
+/// Opens all of `targets` in `browser` in a single launch, if they're all to be opened in the
+/// same app via Launch Services. Returns `None` if that isn't the case, in which case they need to
+/// be opened one by one.
+pub(super) fn open_all_internal(
+    browser: Browser,
+    targets: &[&TargetType],
+    options: &BrowserOptions,
+) -> Option<Result<OpenOutcome>> {
+    let mut app = None;
+    for target in targets {
+        match resolve_launch(browser, target, options) {
+            Ok(Launch::App {
+                app_url,
+                os_handler,
+            }) => match &app {
+                None => app = Some((app_url, os_handler)),
+                Some((first_url, first_os_handler)) => {
+                    if first_url.to_path() != app_url.to_path() || *first_os_handler != os_handler {
+                        return None;
+                    }
                 }
-            } else {
-                create_cf_url(DEFAULT_BROWSER_URL)
-            }
+            },
+            _ => return None,
+        }
+    }
+    let (app_url, os_handler) = app?;
+    Some(open_using_launch_services(
+        browser, &app_url, targets, options, os_handler,
+    ))
+}
+
+/// Works out how `target` is to be opened in `browser`
+fn resolve_launch(
+    browser: Browser,
+    target: &TargetType,
+    options: &BrowserOptions,
+) -> Result<Launch> {
+    // unknown schemes may be handed over to the app registered for them, if so asked
+    let os_handler = browser == Browser::Default
+        && options.unknown_scheme_policy(target) == Some(UnknownSchemePolicy::OsHandler);
+
+    // create the CFUrl for the browser
+    let browser_cf_url = match (browser, options.macos_bundle_id.as_deref()) {
+        (Browser::Default, _) if os_handler => {
+            Some(get_default_app_url(target.as_ref()).ok_or_else(|| {
+                Error::new(ErrorKind::NotFound, "no handler registered for url scheme")
+            })?)
+        }
+        (_, Some(bundle_id)) => Some(get_bundle_app_url(bundle_id).ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("no app found for bundle id {bundle_id}"),
+            )
+        })?),
+        (Browser::Firefox, _) => create_cf_url("file:///Applications/Firefox.app/"),
+        (Browser::Chrome, _) => create_cf_url("file:///Applications/Google Chrome.app/"),
+        (Browser::Opera, _) => create_cf_url("file:///Applications/Opera.app/"),
+        (Browser::Safari, _) => create_cf_url("file:///Applications/Safari.app/"),
+        (Browser::Brave, _) => create_cf_url("file:///Applications/Brave Browser.app/"),
+        (Browser::Edge, _) => create_cf_url("file:///Applications/Microsoft Edge.app/"),
+        (Browser::TorBrowser, _) => create_cf_url("file:///Applications/Tor Browser.app/"),
+        (Browser::Default, _) => {
+            get_default_app_url("https://").or_else(|| create_cf_url(DEFAULT_BROWSER_URL))
         }
-        _ => {
+        (
+            Browser::InternetExplorer
+            | Browser::WebPositive
+            | Browser::Epiphany
+            | Browser::Konqueror,
+            _,
+        ) => {
             return Err(Error::new(
                 ErrorKind::NotFound,
                 "browser not supported on macos",
@@ -50,25 +117,82 @@ pub(super) fn open_browser_internal(
     }
     .ok_or_else(|| Error::new(ErrorKind::Other, "failed to create CFURL"))?;
 
-    let cf_url = create_cf_url(target.as_ref())
-        .ok_or_else(|| Error::new(ErrorKind::Other, "failed to create CFURL"))?;
+    // if the app bundle isn't present, the browser may have been installed as a binary, e.g. via
+    // Homebrew on Apple Silicon
+    if browser != Browser::Default && !browser_cf_url.to_path().map_or(false, |p| p.is_dir()) {
+        let names = options.binary_names(browser);
+        if let Some(binary) = find_browser_binary(&names, &BROWSER_BIN_DIRS) {
+            return Ok(Launch::Binary(binary));
+        }
+    }
+
+    // launch services can't pass arguments to the browser, so for extra arguments, we run the
+    // binary from within the app bundle directly
+    if !options.args.is_empty() && !os_handler {
+        if let Some(binary) = get_bundle_executable(&browser_cf_url) {
+            return Ok(Launch::Binary(binary));
+        }
+    }
+
+    // similarly for a private window, or kiosk mode, if the browser has a flag for it. Tor
+    // Browser's binary is firefox, but it's always private, and doesn't take such flags.
+    if (options.incognito || options.kiosk) && !os_handler && browser != Browser::TorBrowser {
+        if let Some(binary) = get_bundle_executable(&browser_cf_url) {
+            if matches!(
+                BrowserFamily::from_name(&binary.to_string_lossy()),
+                BrowserFamily::Chromium | BrowserFamily::Firefox
+            ) {
+                return Ok(Launch::Binary(binary));
+            }
+        }
+    }
+
+    Ok(Launch::App {
+        app_url: browser_cf_url,
+        os_handler,
+    })
+}
 
-    let urls_v = [cf_url];
+/// Opens `targets` in the app at `app_url` via Launch Services, in a single launch
+fn open_using_launch_services(
+    browser: Browser,
+    app_url: &CFURL,
+    targets: &[&TargetType],
+    options: &BrowserOptions,
+    os_handler: bool,
+) -> Result<OpenOutcome> {
+    let handler_kind = if os_handler {
+        HandlerKind::OsDefault
+    } else {
+        HandlerKind::Browser
+    };
+    let urls_v = targets
+        .iter()
+        .map(|target| {
+            create_target_cf_url(target)
+                .ok_or_else(|| Error::new(ErrorKind::Other, "failed to create CFURL"))
+        })
+        .collect::<Result<Vec<_>>>()?;
     let urls_arr = CFArray::<CFURL>::from_CFTypes(&urls_v);
     let spec = LSLaunchURLSpec {
-        app_url: browser_cf_url.as_concrete_TypeRef(),
+        app_url: app_url.as_concrete_TypeRef(),
         item_urls: urls_arr.as_concrete_TypeRef(),
         pass_thru_params: std::ptr::null(),
-        launch_flags: LS_LAUNCH_FLAG_DEFAULTS | LS_LAUNCH_FLAG_ASYNC,
+        launch_flags: if options.new_window {
+            // this is what `open -n` does, and makes the browser open a new window
+            LS_LAUNCH_FLAG_DEFAULTS | LS_LAUNCH_FLAG_ASYNC | LS_LAUNCH_FLAG_NEW_INSTANCE
+        } else {
+            LS_LAUNCH_FLAG_DEFAULTS | LS_LAUNCH_FLAG_ASYNC
+        },
         async_ref_con: std::ptr::null(),
     };
 
     // handle dry-run scenario
     if options.dry_run {
-        return if let Some(path) = browser_cf_url.to_path() {
+        return if let Some(path) = app_url.to_path() {
             if path.is_dir() {
                 log::debug!("dry-run: not actually opening the browser {}", &browser);
-                Ok(())
+                Ok(OpenOutcome::new(handler_kind, None))
             } else {
                 log::debug!("dry-run: browser {} not found", &browser);
                 Err(Error::new(ErrorKind::NotFound, "browser not found"))
@@ -82,17 +206,323 @@ pub(super) fn open_browser_internal(
     }
 
     // launch the browser
-    log::trace!("about to start browser: {} for {}", &browser, &target);
+    log::trace!(
+        "about to start browser: {} for {} url(s)",
+        &browser,
+        targets.len()
+    );
     let mut launched_app: CFURLRef = std::ptr::null_mut();
     let status = unsafe { LSOpenFromURLSpec(&spec, &mut launched_app) };
     log::trace!("received status: {}", status);
     if status == 0 {
-        Ok(())
+        if !os_handler {
+            position_window(app_url, options);
+        }
+        // launch services doesn't tell us the pid of the launched app
+        Ok(OpenOutcome::new(handler_kind, None))
     } else {
         Err(Error::from(LSError::from(status)))
     }
 }
 
+/// Returns the family of the app registered for `https` urls, without launching it
+pub(super) fn default_browser_family() -> BrowserFamily {
+    default_browser_name()
+        .map(|name| BrowserFamily::from_name(&name))
+        .unwrap_or(BrowserFamily::Unknown)
+}
+
+/// Returns the name of the app bundle registered for `https` urls, e.g. `Firefox`
+pub(super) fn default_browser_name() -> Result<String> {
+    get_default_app_url("https://")
+        .and_then(|app_url| app_url.to_path())
+        .and_then(|path| {
+            path.file_stem()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, "no app registered for https"))
+}
+
+/// Returns the path of the app bundle, or the binary, which would be launched for `browser`
+pub(super) fn browser_path(browser: Browser) -> Result<PathBuf> {
+    let target = TargetType::try_from("https://example.com")?;
+    resolve_internal(browser, &target, &BrowserOptions::default()).map(|resolved| resolved.program)
+}
+
+/// Open `target` via `command`, which is either the bundle id of an app, or the path of an app
+/// bundle or of a binary
+pub(super) fn open_with_command_internal(
+    command: &str,
+    target: &TargetType,
+    options: &BrowserOptions,
+) -> Result<OpenOutcome> {
+    let path = Path::new(command);
+    let app_url = if path.extension().map_or(false, |ext| ext == "app") || path.is_dir() {
+        CFURL::from_path(path, true)
+            .ok_or_else(|| Error::new(ErrorKind::Other, "failed to create CFURL"))?
+    } else if command.contains('/') {
+        return if path.is_file() {
+            open_using_binary(path, target, options)
+        } else {
+            Err(Error::new(
+                ErrorKind::NotFound,
+                format!("{command} not found"),
+            ))
+        };
+    } else {
+        get_bundle_app_url(command).ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("no app found for bundle id {command}"),
+            )
+        })?
+    };
+
+    // launch services can't pass arguments to the app, so we run its binary directly for them
+    if !options.args.is_empty() {
+        if let Some(binary) = get_bundle_executable(&app_url) {
+            return open_using_binary(&binary, target, options);
+        }
+    }
+    open_using_launch_services(Browser::Default, &app_url, &[target], options, false)
+}
+
+/// Returns the app bundle & url, or the binary & its arguments, which would be launched to open
+/// `target` in `browser`
+pub(super) fn resolve_internal(
+    browser: Browser,
+    target: &TargetType,
+    options: &BrowserOptions,
+) -> Result<ResolvedTarget> {
+    match resolve_launch(browser, target, options)? {
+        Launch::Binary(binary) => Ok(ResolvedTarget::from_command(&binary_command(
+            &binary, target, options,
+        ))),
+        Launch::App { app_url, .. } => {
+            let program = app_url
+                .to_path()
+                .filter(|path| path.is_dir())
+                .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("{browser} not found")))?;
+            Ok(ResolvedTarget {
+                program,
+                args: vec![(target as &str).into()],
+            })
+        }
+    }
+}
+
+/// Returns the url of the app with the bundle id `bundle_id`, as preferred by Launch Services, if
+/// there are multiple copies of it installed
+fn get_bundle_app_url(bundle_id: &str) -> Option<CFURL> {
+    let cf_bundle_id = CFString::new(bundle_id);
+    let mut err: CFErrorRef = std::ptr::null_mut();
+    let result = unsafe {
+        LSCopyApplicationURLsForBundleIdentifier(cf_bundle_id.as_concrete_TypeRef(), &mut err)
+    };
+    if result.is_null() {
+        log::debug!(
+            "failed to find app for bundle id {}: {}",
+            bundle_id,
+            unsafe { CFError::wrap_under_create_rule(err) }
+        );
+        return None;
+    }
+    let app_urls = unsafe { CFArray::<CFURL>::wrap_under_create_rule(result) };
+    let app_url = app_urls.get(0).map(|app_url| app_url.clone());
+    log::trace!("app for bundle id {} is {:?}", bundle_id, &app_url);
+    app_url
+}
+
+/// Returns the url of the default app registered with Launch Services for opening `url`
+fn get_default_app_url(url: &str) -> Option<CFURL> {
+    let cf_url = create_cf_url(url)?;
+    let mut err: CFErrorRef = std::ptr::null_mut();
+    let result = unsafe {
+        LSCopyDefaultApplicationURLForURL(cf_url.as_concrete_TypeRef(), LSROLE_VIEWER, &mut err)
+    };
+    if result.is_null() {
+        log::error!("failed to get default app for {}: {}", url, unsafe {
+            CFError::wrap_under_create_rule(err)
+        });
+        None
+    } else {
+        let app_url = unsafe { CFURL::wrap_under_create_rule(result) };
+        log::trace!("default app for {} is {:?}", url, &app_url);
+        Some(app_url)
+    }
+}
+
+/// Directories in which we search for browser binaries, if the app bundle isn't found. These
+/// are where Homebrew installs binaries on Apple Silicon and Intel respectively.
+const BROWSER_BIN_DIRS: [&str; 2] = ["/opt/homebrew/bin", "/usr/local/bin"];
+
+/// Search `dirs` for an executable binary with one of the `names`
+fn find_browser_binary(names: &[&str], dirs: &[&str]) -> Option<PathBuf> {
+    dirs.iter()
+        .flat_map(|dir| names.iter().map(move |name| Path::new(dir).join(name)))
+        .find(|pb| {
+            // metadata follows symlinks, which is how Homebrew links binaries
+            pb.metadata()
+                .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false)
+        })
+}
+
+/// Open `target` by directly running the browser `binary`
+fn open_using_binary(
+    binary: &Path,
+    target: &TargetType,
+    options: &BrowserOptions,
+) -> Result<OpenOutcome> {
+    let mut cmd = binary_command(binary, target, options);
+    if options.dry_run {
+        log::debug!("dry-run: not actually running {:?}", &cmd);
+        return Ok(OpenOutcome::new(HandlerKind::Browser, None));
+    }
+
+    log::trace!("about to run browser binary: {:?}", &cmd);
+    if options.suppress_output {
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+    }
+    let mut child = cmd.spawn()?;
+    if options.wait_for_exit {
+        let status = child.wait()?;
+        log::trace!("browser binary exited: {}", status);
+        return if status.success() {
+            Ok(OpenOutcome::new(HandlerKind::Browser, None))
+        } else {
+            Err(Error::new(
+                ErrorKind::Other,
+                format!("browser exited unsuccessfully ({status})"),
+            ))
+        };
+    }
+    let pid = child.id();
+    crate::retain_child(child);
+    Ok(OpenOutcome::new(HandlerKind::Browser, Some(pid)))
+}
+
+/// Returns the command for opening `target` by running the browser `binary` directly
+fn binary_command(binary: &Path, target: &TargetType, options: &BrowserOptions) -> Command {
+    let mut cmd = Command::new(binary);
+    let family = BrowserFamily::from_name(&binary.to_string_lossy());
+    if options.new_window {
+        match family {
+            // a private window is a new window anyway, and firefox wants the url right after it
+            BrowserFamily::Firefox if !options.incognito => {
+                cmd.arg("-new-window");
+            }
+            BrowserFamily::Chromium => {
+                cmd.arg("--new-window");
+            }
+            _ => {}
+        }
+    }
+    if options.incognito {
+        if let Some(arg) = family.incognito_arg() {
+            cmd.arg(arg);
+        }
+    }
+    if options.kiosk && matches!(family, BrowserFamily::Chromium | BrowserFamily::Firefox) {
+        cmd.arg("--kiosk");
+    }
+    if let Some((x, y, width, height)) = window_bounds(options) {
+        if family == BrowserFamily::Chromium {
+            cmd.arg(format!("--window-position={x},{y}"));
+            cmd.arg(format!("--window-size={width},{height}"));
+        }
+    }
+    cmd.args(&options.args);
+    cmd.arg(target as &str);
+    crate::apply_command_hook(&mut cmd);
+    cmd
+}
+
+/// Apps which support setting the bounds of their windows via AppleScript
+const SCRIPTABLE_APPS: [&str; 2] = ["Safari", "Google Chrome"];
+
+/// Returns the window bounds asked for in `options`, unless overridden by another window mode
+fn window_bounds(options: &BrowserOptions) -> Option<(i32, i32, u32, u32)> {
+    if options.kiosk || options.fullscreen || options.maximized {
+        None
+    } else {
+        options.window_bounds
+    }
+}
+
+/// Sets the bounds of the front window of the app at `app_url` in the background, if asked for
+/// in `options`, and if the app is scriptable. This is best effort, so failures are only logged.
+fn position_window(app_url: &CFURL, options: &BrowserOptions) {
+    let bounds = match window_bounds(options) {
+        Some(bounds) => bounds,
+        None => return,
+    };
+    let app = app_url
+        .to_path()
+        .and_then(|path| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default();
+    if !SCRIPTABLE_APPS.contains(&app.as_str()) {
+        log::debug!("can't set the window bounds of {:?}", app);
+        return;
+    }
+
+    let mut cmd = window_bounds_command(&app, bounds);
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    log::trace!("about to position window: {:?}", &cmd);
+    match cmd.spawn() {
+        // reap the script once done, without holding up the caller
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(err) => log::debug!("failed to run osascript: {}", err),
+    }
+}
+
+/// Returns the `osascript` command which sets the bounds of the front window of `app`, waiting
+/// for a few seconds for it to show up, if the app doesn't have a window yet
+fn window_bounds_command(app: &str, (x, y, width, height): (i32, i32, u32, u32)) -> Command {
+    let (right, bottom) = (
+        i64::from(x) + i64::from(width),
+        i64::from(y) + i64::from(height),
+    );
+    let script = format!(
+        "tell application \"{app}\"
+            repeat 50 times
+                if (count of windows) > 0 then exit repeat
+                delay 0.1
+            end repeat
+            set bounds of front window to {{{x}, {y}, {right}, {bottom}}}
+        end tell"
+    );
+    let mut cmd = Command::new("osascript");
+    cmd.arg("-e").arg(script);
+    cmd
+}
+
+/// Returns the path of the executable within the app bundle at `app_url`
+fn get_bundle_executable(app_url: &CFURL) -> Option<PathBuf> {
+    CFBundle::new(app_url.clone())?.executable_url()?.to_path()
+}
+
+/// Create a Core Foundation CFURL object for the `target` url, retaining its query & fragment.
+///
+/// CFURL rejects strings with characters which aren't legal in a url, e.g. non-ascii ones or a
+/// second `#`, which a raw url may well have. In such cases, we fall back to the normalized url,
+/// which has such characters percent encoded, instead of failing, or losing the fragment.
+fn create_target_cf_url(target: &TargetType) -> Option<CFURL> {
+    create_cf_url(target.as_ref()).or_else(|| {
+        log::debug!("falling back to normalized url for CFURL: {}", target.0);
+        create_cf_url(target.0.as_str())
+    })
+}
+
 /// Create a Core Foundation CFURL object given a rust-y `url`
 fn create_cf_url(url: &str) -> Option<CFURL> {
     let url_u8 = url.as_bytes();
@@ -169,6 +599,7 @@ const LSROLE_VIEWER: LSRolesMask = 0x00000002;
 // as per https://developer.apple.com/documentation/coreservices/lslaunchflags/klslaunchdefaults?language=objc
 const LS_LAUNCH_FLAG_DEFAULTS: u32 = 0x00000001;
 const LS_LAUNCH_FLAG_ASYNC: u32 = 0x00010000;
+const LS_LAUNCH_FLAG_NEW_INSTANCE: u32 = 0x00080000;
 
 #[repr(C)]
 struct LSLaunchURLSpec {
@@ -190,6 +621,13 @@ extern "C" {
         outError: *mut CFErrorRef,
     ) -> CFURLRef;
 
+    /// Used to find an app by its bundle id, bypassing hardcoded paths. See:
+    /// https://developer.apple.com/documentation/coreservices/1449290-lscopyapplicationurlsforbundlei?language=objc
+    fn LSCopyApplicationURLsForBundleIdentifier(
+        inBundleIdentifier: CFStringRef,
+        outError: *mut CFErrorRef,
+    ) -> CFArrayRef;
+
     /// Used to launch the browser to open a url
     /// https://developer.apple.com/documentation/coreservices/1441986-lsopenfromurlspec?language=objc
     fn LSOpenFromURLSpec(
@@ -219,6 +657,91 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_find_browser_binary() {
+        let dir = std::env::temp_dir().join(format!("webbrowser-test-brew.{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("failed to create dir");
+        let binary = dir.join("firefox");
+        std::fs::write(&binary, "#!/bin/sh\n").expect("failed to create stub");
+        std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755))
+            .expect("failed to set permissions");
+        // a non-executable file shouldn't be picked up
+        std::fs::write(dir.join("opera"), "").expect("failed to create stub");
+
+        let dir_str = dir.to_string_lossy().into_owned();
+        let dirs = ["/non-existent/bin", dir_str.as_str()];
+        let options = BrowserOptions::new();
+        let firefox = find_browser_binary(&options.binary_names(Browser::Firefox), &dirs);
+        let opera = find_browser_binary(&options.binary_names(Browser::Opera), &dirs);
+        let safari = find_browser_binary(&options.binary_names(Browser::Safari), &dirs);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(firefox, Some(binary));
+        assert_eq!(opera, None);
+        assert_eq!(safari, None);
+    }
+
+    #[test]
+    fn test_cf_url_retains_fragment() {
+        let target = TargetType::try_from("https://example.com/page?q=a&r=b#section")
+            .expect("failed to parse url");
+        let cf_url = create_target_cf_url(&target).expect("failed to create CFURL");
+        assert_eq!(
+            cf_url.get_string().to_string(),
+            "https://example.com/page?q=a&r=b#section"
+        );
+
+        // a raw url which CFURL can't parse, falls back to the normalized one
+        let mut target = TargetType::try_from("https://example.com/päge?q=ü#séction")
+            .expect("failed to parse url");
+        target.set_raw("https://example.com/päge?q=ü#séction#more");
+        let cf_url = create_target_cf_url(&target).expect("failed to create CFURL");
+        let url = cf_url.get_string().to_string();
+        assert!(url.contains("?q="));
+        assert!(url.contains("#s"));
+    }
+
+    #[test]
+    fn test_window_bounds_command() {
+        let cmd = window_bounds_command("Google Chrome", (-10, 20, 800, 600));
+        assert_eq!(cmd.get_program(), "osascript");
+        let args: Vec<_> = cmd.get_args().map(|arg| arg.to_string_lossy()).collect();
+        assert_eq!(args.len(), 2);
+        assert_eq!(args[0], "-e");
+        assert!(args[1].starts_with("tell application \"Google Chrome\""));
+        assert!(args[1].contains("set bounds of front window to {-10, 20, 790, 620}"));
+
+        // other window modes take precedence
+        let mut options = BrowserOptions::new();
+        options.with_window_position(0, 0, 800, 600);
+        assert_eq!(window_bounds(&options), Some((0, 0, 800, 600)));
+        options.with_maximized(true);
+        assert_eq!(window_bounds(&options), None);
+    }
+
+    #[test]
+    fn test_bundle_id() {
+        let _ = env_logger::try_init();
+        let app_url = get_bundle_app_url("com.apple.Safari").expect("safari not found");
+        let path = app_url.to_path().expect("invalid app url");
+        assert!(path.is_dir(), "{} isn't an app bundle", path.display());
+        assert!(path.ends_with("Safari.app"));
+        assert!(get_bundle_app_url("com.example.non-existent").is_none());
+
+        // the bundle id takes precedence over the browser
+        let target = TargetType::try_from("https://github.com").expect("failed to parse url");
+        let mut options = BrowserOptions::new();
+        options
+            .with_dry_run(true)
+            .with_macos_bundle_id("com.apple.Safari");
+        assert!(open_browser_internal(Browser::Opera, &target, &options).is_ok());
+        options.with_macos_bundle_id("com.example.non-existent");
+        let err = open_browser_internal(Browser::Safari, &target, &options)
+            .expect_err("non-existent bundle id resolved");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
     #[test]
     fn test_existence() {
         let _ = env_logger::try_init();
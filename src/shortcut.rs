@@ -0,0 +1,167 @@
+//! Support for following `.webloc` (macOS) and `.url` (Windows) shortcut files to the url
+//! embedded in them, so that they get opened in the browser, instead of say a text editor.
+
+use crate::{ErrorKind, Result};
+use std::io::Error;
+use std::path::Path;
+
+/// If `path` is an existing `.webloc` or `.url` shortcut file, returns the url embedded in it,
+/// else returns `None`. An error is returned if the shortcut file can't be read or parsed.
+pub(crate) fn get_shortcut_url(path: &Path) -> Result<Option<String>> {
+    let ext = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => ext.to_ascii_lowercase(),
+        None => return Ok(None),
+    };
+    if !matches!(ext.as_str(), "webloc" | "url") || !path.is_file() {
+        return Ok(None);
+    }
+
+    let contents = std::fs::read(path)?;
+    let url = if ext == "url" {
+        parse_url_file(&String::from_utf8_lossy(&contents))
+    } else if contents.starts_with(b"bplist") {
+        parse_webloc_file(&binary_plist_to_xml(path)?)
+    } else {
+        parse_webloc_file(&String::from_utf8_lossy(&contents))
+    };
+    match url {
+        Some(url) => {
+            log::debug!("following shortcut {:?} to {}", path, &url);
+            Ok(Some(url))
+        }
+        None => Err(Error::new(
+            ErrorKind::InvalidData,
+            "no url found in shortcut file",
+        )),
+    }
+}
+
+/// Parses the ini style contents of a windows `.url` file, which look like:
+/// ```text
+/// [InternetShortcut]
+/// URL=https://example.com/
+/// ```
+fn parse_url_file(contents: &str) -> Option<String> {
+    let mut in_section = false;
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_section = line.eq_ignore_ascii_case("[InternetShortcut]");
+        } else if in_section {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim().eq_ignore_ascii_case("URL") && !value.trim().is_empty() {
+                    return Some(value.trim().to_owned());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Parses the xml plist contents of a macOS `.webloc` file, which look like:
+/// ```text
+/// <plist version="1.0"><dict><key>URL</key><string>https://example.com/</string></dict></plist>
+/// ```
+fn parse_webloc_file(contents: &str) -> Option<String> {
+    let after_key = &contents[contents.find("<key>URL</key>")? + "<key>URL</key>".len()..];
+    let value = after_key.trim_start().strip_prefix("<string>")?;
+    let value = &value[..value.find("</string>")?];
+    let url = value
+        .trim()
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+    if url.is_empty() {
+        None
+    } else {
+        Some(url)
+    }
+}
+
+/// Converts a binary plist to xml, using `plutil`
+#[cfg(target_os = "macos")]
+fn binary_plist_to_xml(path: &Path) -> Result<String> {
+    let output = std::process::Command::new("plutil")
+        .args(["-convert", "xml1", "-o", "-"])
+        .arg(path)
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            "failed to convert binary plist",
+        ))
+    }
+}
+
+/// Binary plists are supported only on macOS, where we've `plutil` to convert them
+#[cfg(not(target_os = "macos"))]
+fn binary_plist_to_xml(_path: &Path) -> Result<String> {
+    Err(Error::new(
+        ErrorKind::InvalidData,
+        "binary .webloc files are supported only on macOS",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url_file() {
+        let contents = "[DEFAULT]\r\nBASEURL=https://wrong.example.com/\r\n\
+            [InternetShortcut]\r\nIDList=\r\nURL=https://example.com/a?b=c&d=e\r\nIconIndex=0\r\n";
+        assert_eq!(
+            parse_url_file(contents).as_deref(),
+            Some("https://example.com/a?b=c&d=e")
+        );
+        assert_eq!(parse_url_file("[InternetShortcut]\nIconIndex=0\n"), None);
+    }
+
+    #[test]
+    fn test_parse_webloc_file() {
+        let contents = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>URL</key>
+	<string>https://example.com/a?b=c&amp;d=e</string>
+</dict>
+</plist>
+"#;
+        assert_eq!(
+            parse_webloc_file(contents).as_deref(),
+            Some("https://example.com/a?b=c&d=e")
+        );
+        assert_eq!(parse_webloc_file("<plist><dict></dict></plist>"), None);
+    }
+
+    #[test]
+    fn test_get_shortcut_url() {
+        let dir = std::env::temp_dir();
+        let url_file = dir.join(format!("webbrowser-test-{}.URL", std::process::id()));
+        std::fs::write(&url_file, "[InternetShortcut]\nURL=https://example.com/\n")
+            .expect("failed to write shortcut file");
+        let url = get_shortcut_url(&url_file);
+        let _ = std::fs::remove_file(&url_file);
+        assert_eq!(
+            url.expect("failed to read shortcut").as_deref(),
+            Some("https://example.com/")
+        );
+
+        // non-existent or non-shortcut files are left alone
+        assert_eq!(
+            get_shortcut_url(&dir.join("webbrowser-test-missing.webloc"))
+                .expect("failed to check missing file"),
+            None
+        );
+        assert_eq!(
+            get_shortcut_url(Path::new("Cargo.toml")).expect("failed to check non-shortcut"),
+            None
+        );
+    }
+}
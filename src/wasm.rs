@@ -1,4 +1,8 @@
-use crate::{Browser, BrowserOptions, Error, ErrorKind, Result, TargetType};
+use crate::{
+    Browser, BrowserFamily, BrowserOptions, ErrorKind, HandlerKind, OpenOutcome, ResolvedTarget,
+    Result, TargetType,
+};
+use std::io::Error;
 
 /// Deal with opening a URL in wasm32. This implementation ignores the browser attribute
 /// and always opens URLs in the same browser where wasm32 vm is running.
@@ -6,14 +10,14 @@ pub(super) fn open_browser_internal(
     _: Browser,
     target: &TargetType,
     options: &BrowserOptions,
-) -> Result<()> {
+) -> Result<OpenOutcome> {
     // ensure we're opening only http/https urls, failing otherwise
     let url = target.get_http_url()?;
 
     // always return true for a dry run
     if options.dry_run {
         if web_sys::window().is_some() {
-            return Ok(());
+            return Ok(OpenOutcome::new(HandlerKind::Browser, None));
         } else {
             return Err(Error::new(ErrorKind::Other, "no browser window available"));
         }
@@ -23,10 +27,10 @@ pub(super) fn open_browser_internal(
     match window {
         Some(w) => match w.open_with_url_and_target(url, &options.target_hint) {
             Ok(x) => match x {
-                Some(_) => Ok(()),
+                Some(_) => Ok(OpenOutcome::new(HandlerKind::Browser, None)),
                 None => {
                     wasm_console_log(POPUP_ERR_MSG, options);
-                    Err(Error::new(ErrorKind::Other, POPUP_ERR_MSG))
+                    Err(Error::new(ErrorKind::Other, crate::Error::PopupBlocked))
                 }
             },
             Err(_) => {
@@ -38,6 +42,51 @@ pub(super) fn open_browser_internal(
     }
 }
 
+/// The default browser can't be identified on this platform
+pub(super) fn default_browser_family() -> BrowserFamily {
+    BrowserFamily::Unknown
+}
+
+/// The default browser can't be identified on this platform
+pub(super) fn default_browser_name() -> Result<String> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "default browser can't be identified on this platform",
+    ))
+}
+
+/// Browsers aren't launched via executables on this platform
+pub(super) fn browser_path(_browser: Browser) -> Result<std::path::PathBuf> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "browser executables can't be resolved on this platform",
+    ))
+}
+
+/// Urls can't be opened via commands on this platform
+pub(super) fn open_with_command_internal(
+    _command: &str,
+    _target: &TargetType,
+    _options: &BrowserOptions,
+) -> Result<OpenOutcome> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "urls can't be opened via commands on this platform",
+    ))
+}
+
+/// Urls aren't opened by running commands on this platform
+pub(super) fn resolve_internal(
+    _browser: Browser,
+    _target: &TargetType,
+    _options: &BrowserOptions,
+) -> Result<ResolvedTarget> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "commands can't be resolved on this platform",
+    ))
+}
+
 /// Print to browser console
 fn wasm_console_log(_msg: &str, _options: &BrowserOptions) {
     #[cfg(all(debug_assertions, feature = "wasm-console"))]
@@ -129,6 +129,29 @@ where
     let _ = std::fs::remove_file(&pb);
 }
 
+#[allow(dead_code)]
+pub async fn check_shortcut_file(platform: &str, ext: &str) {
+    let cwd = std::env::current_dir().expect("unable to determine current dir");
+    let tmpdir = cwd.join("target").join("tmp");
+    let _ = std::fs::create_dir_all(&tmpdir);
+    let id = rand::thread_rng().next_u32();
+    let pb = tmpdir.join(format!("test.{id}.{ext}"));
+    let uri = format!("/{platform}/shortcut/{ext}?r={id}");
+    check_request_received_using(uri, "127.0.0.1", |url, _port| {
+        let contents = match ext {
+            "url" => format!("[InternetShortcut]\r\nURL={url}\r\n"),
+            _ => format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<plist version=\"1.0\">\n<dict>\n\t<key>URL</key>\n\t<string>{url}</string>\n</dict>\n</plist>\n"
+            ),
+        };
+        std::fs::write(&pb, contents).expect("failed to write shortcut file");
+        open_browser(Browser::Default, &pb.as_os_str().to_string_lossy())
+            .expect("failed to open shortcut file");
+    })
+    .await;
+    let _ = std::fs::remove_file(&pb);
+}
+
 #[allow(dead_code)]
 pub async fn check_browser(browser: Browser, platform: &str) {
     check_request_received(browser, format!("/{platform}")).await;
@@ -97,4 +97,14 @@ mod tests {
     fn test_non_existence_safari() {
         assert!(!Browser::Safari.exists(), "should not have found Safari");
     }
+
+    #[test]
+    fn test_non_existence_brave() {
+        assert!(!Browser::Brave.exists(), "should not have found Brave");
+    }
+
+    #[test]
+    fn test_non_existence_edge() {
+        assert!(!Browser::Edge.exists(), "should not have found Edge");
+    }
 }
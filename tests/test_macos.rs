@@ -18,6 +18,11 @@ mod tests {
         check_browser(Browser::Safari, TEST_PLATFORM).await;
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_open_webloc_shortcut() {
+        check_shortcut_file(TEST_PLATFORM, "webloc").await;
+    }
+
     // #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     // #[ignore]
     // async fn test_open_firefox() {
@@ -48,6 +53,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_non_existence_brave() {
+        assert!(!Browser::Brave.exists(), "should not have found Brave");
+    }
+
+    #[test]
+    fn test_non_existence_edge() {
+        assert!(!Browser::Edge.exists(), "should not have found Edge");
+    }
+
     #[cfg(not(feature = "hardened"))]
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_local_file_abs_path() {
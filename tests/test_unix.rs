@@ -15,6 +15,29 @@ mod tests {
         check_browser(Browser::Default, TEST_PLATFORM).await;
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[serial]
+    async fn test_open_with_fallback() {
+        let uri = format!("/{TEST_PLATFORM}/fallback");
+        check_request_received_using(uri, "127.0.0.1", |url, _port| {
+            webbrowser::open_with_fallback("webbrowser-test://deep/link", url)
+                .expect("failed to open fallback url");
+        })
+        .await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[serial]
+    async fn test_open_url_shortcut() {
+        check_shortcut_file(TEST_PLATFORM, "url").await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    #[serial]
+    async fn test_open_webloc_shortcut() {
+        check_shortcut_file(TEST_PLATFORM, "webloc").await;
+    }
+
     #[test]
     fn test_existence_default() {
         assert!(Browser::is_available(), "should have found a browser");
@@ -25,6 +48,16 @@ mod tests {
         assert!(!Browser::Safari.exists(), "should not have found Safari");
     }
 
+    #[test]
+    fn test_non_existence_brave() {
+        assert!(!Browser::Brave.exists(), "should not have found Brave");
+    }
+
+    #[test]
+    fn test_non_existence_edge() {
+        assert!(!Browser::Edge.exists(), "should not have found Edge");
+    }
+
     #[cfg(not(feature = "hardened"))]
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     #[serial]
@@ -13,6 +13,11 @@ mod tests {
         check_browser(Browser::Default, TEST_PLATFORM).await;
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_open_url_shortcut() {
+        check_shortcut_file(TEST_PLATFORM, "url").await;
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     #[ignore]
     async fn test_open_internet_explorer() {
@@ -29,6 +34,16 @@ mod tests {
         assert!(!Browser::Safari.exists(), "should not have found Safari");
     }
 
+    #[test]
+    fn test_non_existence_brave() {
+        assert!(!Browser::Brave.exists(), "should not have found Brave");
+    }
+
+    #[test]
+    fn test_existence_edge() {
+        assert!(Browser::Edge.exists(), "should have found Edge");
+    }
+
     #[cfg(not(feature = "hardened"))]
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_local_file_abs_path() {